@@ -1,41 +1,738 @@
-use std::io::{stdout, BufWriter};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write, stdout},
+    path::{Path, PathBuf},
+};
+
+use clap::{CommandFactory, Parser, ValueEnum, error::ErrorKind};
 
+#[cfg(feature = "serde")]
+use crate::{cli::RecoverArgs, io::journal};
 use crate::{
-    common::error::AppError,
+    cli::{Cli, Command, InputArgs, InputFormat, OutputFormat, ParseErrorMode, ProcessArgs},
+    common::{
+        error::{AppError, exit_code},
+        money::RoundingPolicy,
+        outcome::Outcome,
+    },
     domain::ledger::Ledger,
-    io::{reader, writer},
+    io::{
+        atomic::AtomicFile,
+        compression::{self, Compression, Encoder},
+        reader::{self, InputRow},
+        rejects::{self, RejectWriter},
+        source,
+        writer::{self, ReportOptions},
+    },
+    worker::processor::Processor,
 };
 
-pub fn run<I, S>(args: I) -> Result<(), AppError>
+/// What a successful [`run`] did, beyond writing the balances.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    /// Malformed rows skipped under a lenient [`ParseErrorMode`].
+    pub skipped_rows: usize,
+    /// Amounts rounded to their currency's minor units (4 decimal places
+    /// without a currency) under the [`RoundingPolicy`].
+    pub rounded_amounts: usize,
+}
+
+impl RunSummary {
+    /// A run that skipped rows only partially succeeded.
+    pub fn is_partial(&self) -> bool {
+        self.skipped_rows > 0
+    }
+
+    /// Exit code the binary reports for this run; see [`exit_code`].
+    pub fn exit_code(&self) -> u8 {
+        if self.is_partial() {
+            exit_code::PARTIAL
+        } else {
+            exit_code::SUCCESS
+        }
+    }
+}
+
+/// A malformed input row that was skipped under a lenient [`ParseErrorMode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SkippedRow {
+    source: String,
+    line: u64,
+    error: String,
+}
+
+/// An input that could not be read to the end. Unlike a malformed row, it
+/// stops the run in every [`ParseErrorMode`].
+fn read_error(source: &str, e: io::Error) -> AppError {
+    AppError::OpenInput(io::Error::new(e.kind(), format!("{source}: {e}")))
+}
+
+/// Feeds the rows of one input through the processor, writing skipped and
+/// invalid rows to `rejects` when given. Malformed rows skipped under a
+/// lenient [`ParseErrorMode`] are appended to `skipped`; an error reading the
+/// input fails the run whatever the mode.
+///
+/// Returns how many amounts were rounded.
+fn process_rows<W: Write>(
+    source: &str,
+    rows: impl Iterator<Item = io::Result<InputRow>>,
+    ledger: &mut Ledger,
+    processor: &mut Processor,
+    on_parse_error: ParseErrorMode,
+    mut rejects: Option<&mut RejectWriter<W>>,
+    skipped: &mut Vec<SkippedRow>,
+) -> Result<usize, AppError> {
+    let mut rounded_amounts = 0;
+    for row in rows {
+        let InputRow {
+            line,
+            raw,
+            event,
+            rounded,
+        } = match row {
+            Ok(row) => row,
+            Err(e) => {
+                if let Some(rejects) = rejects.as_mut() {
+                    rejects.flush().map_err(AppError::Output)?;
+                }
+                return Err(read_error(source, e));
+            }
+        };
+        rounded_amounts += usize::from(rounded);
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                // Record the offending row before deciding whether to go on.
+                if let Some(rejects) = rejects.as_mut() {
+                    rejects
+                        .write(source, line, &raw, rejects::PARSE_ERROR, &e)
+                        .map_err(AppError::Output)?;
+                }
+                if on_parse_error == ParseErrorMode::Abort {
+                    if let Some(rejects) = rejects.as_mut() {
+                        rejects.flush().map_err(AppError::Output)?;
+                    }
+                    return Err(AppError::Parse(format!("{source}:{line}: {e}")));
+                }
+                skipped.push(SkippedRow {
+                    source: source.to_string(),
+                    line,
+                    error: e,
+                });
+                continue;
+            }
+        };
+        if let Outcome::Rejected(reason) = processor.process(ledger, event)?
+            && let Some(rejects) = rejects.as_mut()
+        {
+            rejects
+                .write(source, line, &raw, reason.code(), &reason.to_string())
+                .map_err(AppError::Output)?;
+        }
+    }
+
+    if let Some(rejects) = rejects.as_mut() {
+        rejects.flush().map_err(AppError::Output)?;
+    }
+    Ok(rounded_amounts)
+}
+
+/// Parses the command line and runs the selected command.
+///
+/// Kept free of `std::process` concerns so it can be called from tests;
+/// `--help` and `--version` come back as [`AppError::Cli`].
+pub fn run<I, S>(args: I) -> Result<RunSummary, AppError>
 where
     I: IntoIterator<Item = S>,
     S: Into<String>,
 {
     let args: Vec<String> = args.into_iter().map(|s| s.into()).collect();
-    if args.len() < 2 {
+    match Cli::try_parse_from(args)?.into_command() {
+        Command::Process(args) => process(&args),
+        Command::Validate(args) => validate(&args),
+        Command::Inspect(args) => inspect(&args),
+        #[cfg(feature = "serde")]
+        Command::Recover(args) => recover(&args),
+    }
+}
+
+/// Inputs to read, in order. Stdin can only be read once.
+fn inputs(args: &InputArgs) -> Result<&[PathBuf], AppError> {
+    let inputs = args.inputs();
+    if inputs.is_empty() {
         return Err(AppError::MissingArg);
     }
-    let input_path = &args[1];
+    if inputs.iter().filter(|p| source::is_stdin(p)).count() > 1 {
+        return Err(Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "stdin (-) can only be read once",
+            )
+            .into());
+    }
+    Ok(inputs)
+}
 
-    let file = std::fs::File::open(input_path)?;
-    let mut reader = csv::ReaderBuilder::new()
-        .trim(csv::Trim::All)
-        .flexible(true)
-        .from_reader(file);
-    let transactions = reader::read_transactions(&mut reader);
+/// One opened input, yielding rows in its format.
+enum InputReader {
+    Csv(csv::Reader<BufReader<Box<dyn Read>>>),
+    #[cfg(feature = "serde")]
+    Jsonl(BufReader<Box<dyn Read>>),
+}
 
-    let mut ledger = Ledger::new();
-    let mut processor = crate::worker::processor::Processor::new();
+impl InputReader {
+    /// Opens one input, decompressing gzip/zstd transparently and detecting
+    /// the format when it is `auto`.
+    fn open(path: &Path, format: InputFormat) -> Result<Self, AppError> {
+        let mut input = BufReader::new(compression::decoder(source::open(path)?)?);
+        let format = match format {
+            InputFormat::Auto => detect_format(path, &mut input)?,
+            format => format,
+        };
+        Ok(match format {
+            #[cfg(feature = "serde")]
+            InputFormat::Jsonl => InputReader::Jsonl(input),
+            #[cfg(not(feature = "serde"))]
+            InputFormat::Jsonl => {
+                return Err(needs_serde(&format!(
+                    "{}: JSON Lines input",
+                    path.display()
+                )));
+            }
+            InputFormat::Csv | InputFormat::Auto => InputReader::Csv(
+                csv::ReaderBuilder::new()
+                    .trim(csv::Trim::All)
+                    .flexible(true)
+                    .from_reader(input),
+            ),
+        })
+    }
 
-    for event in transactions {
-        let event = event.map_err(AppError::Parse)?;
-        processor.process(&mut ledger, event)?;
+    fn rows(
+        &mut self,
+        rounding: RoundingPolicy,
+    ) -> Box<dyn Iterator<Item = io::Result<InputRow>> + '_> {
+        match self {
+            InputReader::Csv(rdr) => Box::new(reader::read_rows(rdr, rounding)),
+            #[cfg(feature = "serde")]
+            InputReader::Jsonl(rdr) => Box::new(reader::read_jsonl_rows(rdr, rounding)),
+        }
     }
+}
 
-    // After processing all transactions, write the ledger state to stdout
-    let stdout = stdout();
-    let writer = BufWriter::new(stdout.lock());
-    writer::write_accounts(writer, ledger.accounts())?;
+/// A usage error for `what`, which this build left out.
+#[cfg(not(feature = "serde"))]
+fn needs_serde(what: &str) -> AppError {
+    Cli::command()
+        .error(
+            ErrorKind::InvalidValue,
+            format!("{what} needs the `serde` feature"),
+        )
+        .into()
+}
+
+/// Picks JSONL for `.jsonl`/`.ndjson` files (also when compressed) or when
+/// the data starts with `{`; CSV otherwise.
+fn detect_format<R: Read>(path: &Path, input: &mut BufReader<R>) -> Result<InputFormat, AppError> {
+    let name = match Compression::from_extension(path) {
+        Compression::None => path.to_path_buf(),
+        _ => path.with_extension(""),
+    };
+    match name.extension().and_then(|e| e.to_str()) {
+        Some("jsonl" | "ndjson") => return Ok(InputFormat::Jsonl),
+        Some("csv") => return Ok(InputFormat::Csv),
+        _ => {}
+    }
+
+    let first = input.fill_buf()?.iter().find(|b| !b.is_ascii_whitespace());
+    Ok(match first {
+        Some(b'{') => InputFormat::Jsonl,
+        _ => InputFormat::Csv,
+    })
+}
+
+fn process(args: &ProcessArgs) -> Result<RunSummary, AppError> {
+    let inputs = inputs(&args.input)?;
+    #[cfg(not(feature = "serde"))]
+    if args.output_format != OutputFormat::Csv {
+        return Err(needs_serde("JSON output"));
+    }
+
+    let mut rejects = match &args.rejects {
+        Some(path) => Some(RejectWriter::create(path).map_err(AppError::Output)?),
+        None => None,
+    };
+
+    // Every input is applied, in order, to the same ledger.
+    let mut ledger = initial_ledger(args)?;
+    let mut processor = new_processor(args)?;
+    let mut skipped = Vec::new();
+    let mut rounded_amounts = 0;
+    for path in inputs {
+        let mut reader = InputReader::open(path, args.input.input_format)?;
+        rounded_amounts += process_rows(
+            &path.to_string_lossy(),
+            reader.rows(args.input.rounding),
+            &mut ledger,
+            &mut processor,
+            args.on_parse_error,
+            rejects.as_mut(),
+            &mut skipped,
+        )?;
+    }
+
+    processor.finish()?;
+
+    // After processing all transactions, write the ledger state to the report
+    write_report(args, &ledger)?;
+    if let Some(path) = &args.totals {
+        write_csv_file(path, |out| writer::write_totals(out, &ledger.totals()))?;
+    }
+    let negative = ledger.negative_available();
+    if let Some(path) = &args.negative_available_report {
+        write_csv_file(path, |out| writer::write_negative_available(out, &negative))?;
+    }
+    #[cfg(feature = "serde")]
+    if let Some(path) = &args.save_state {
+        ledger.save_snapshot(path).map_err(AppError::Output)?;
+    }
+
+    if rounded_amounts > 0 {
+        eprintln!(
+            "rounded {rounded_amounts} amount(s) to the currency's minor units ({})",
+            args.input.rounding.to_possible_value().unwrap().get_name()
+        );
+    }
+    if !negative.is_empty() {
+        eprintln!(
+            "{} balance(s) ended with a negative available amount",
+            negative.len()
+        );
+    }
+    if !skipped.is_empty() {
+        eprintln!("skipped {} malformed row(s)", skipped.len());
+        if args.on_parse_error == ParseErrorMode::Collect {
+            for row in &skipped {
+                eprintln!("  {}:{}: {}", row.source, row.line, row.error);
+            }
+        }
+    }
+
+    Ok(RunSummary {
+        skipped_rows: skipped.len(),
+        rounded_amounts,
+    })
+}
+
+/// The ledger to continue from: the `--load-state` snapshot, or an empty one.
+#[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+fn initial_ledger(args: &ProcessArgs) -> Result<Ledger, AppError> {
+    #[cfg(feature = "serde")]
+    if let Some(path) = &args.load_state {
+        return Ledger::load_snapshot(path);
+    }
+    Ok(Ledger::new())
+}
+
+/// A processor applying the policy flags, journaling to `--journal` when given.
+fn new_processor(args: &ProcessArgs) -> Result<Processor, AppError> {
+    let processor = Processor::new().with_policy(args.policy);
+    #[cfg(feature = "serde")]
+    if let Some(path) = &args.journal {
+        let journal = journal::Journal::create(path, args.journal_fsync, &args.policy)
+            .map_err(AppError::Output)?;
+        return Ok(processor.with_journal(journal));
+    }
+    Ok(processor)
+}
+
+/// Writes the balances in `--output-format` to `--output` (atomically) or to
+/// stdout, compressed as requested by `--compress` or, failing that, by the
+/// output file extension.
+fn write_report(args: &ProcessArgs, ledger: &Ledger) -> Result<(), AppError> {
+    let compression = args.compress.unwrap_or_else(|| {
+        args.output
+            .as_deref()
+            .map_or(Compression::None, Compression::from_extension)
+    });
+
+    match &args.output {
+        Some(path) => {
+            // Readers of `path` never see a half-written report.
+            let out = AtomicFile::create(path).map_err(AppError::Output)?;
+            let mut out = Encoder::new(out, compression).map_err(AppError::Output)?;
+            write_balances(&mut out, args, ledger)?;
+            out.finish()
+                .and_then(AtomicFile::commit)
+                .map_err(AppError::Output)?;
+        }
+        None => {
+            let stdout = stdout();
+            let out = BufWriter::new(stdout.lock());
+            let mut out = Encoder::new(out, compression).map_err(AppError::Output)?;
+            write_balances(&mut out, args, ledger)?;
+            out.finish().map_err(AppError::Output)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a secondary CSV report to `path`; errors name the file.
+fn write_csv_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), csv::Error>,
+) -> Result<(), AppError> {
+    let with_path = |e: io::Error| {
+        AppError::Output(io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+    };
+    let mut out = BufWriter::new(File::create(path).map_err(with_path)?);
+    write(&mut out).map_err(|e| with_path(e.into()))?;
+    out.flush().map_err(with_path)
+}
 
+fn write_balances<W: Write>(out: W, args: &ProcessArgs, ledger: &Ledger) -> Result<(), AppError> {
+    let accounts = ledger.accounts();
+    let options = ReportOptions {
+        status: args.status_column,
+    };
+    match args.output_format {
+        OutputFormat::Csv => writer::write_accounts(out, accounts, options)?,
+        #[cfg(feature = "serde")]
+        OutputFormat::Json => writer::write_accounts_json(out, accounts, options)
+            .map_err(|e| AppError::Output(e.into()))?,
+        #[cfg(feature = "serde")]
+        OutputFormat::Jsonl => writer::write_accounts_jsonl(out, accounts, options)
+            .map_err(|e| AppError::Output(e.into()))?,
+        // Refused by `process` before any input is read.
+        #[cfg(not(feature = "serde"))]
+        OutputFormat::Json | OutputFormat::Jsonl => return Err(needs_serde("JSON output")),
+    }
     Ok(())
 }
+
+/// Parses every row of every input and lists the malformed ones on stdout.
+fn validate(args: &InputArgs) -> Result<RunSummary, AppError> {
+    let inputs = inputs(args)?;
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    let mut rows = 0usize;
+    let mut malformed = 0usize;
+    let mut rounded = 0usize;
+    for path in inputs {
+        let mut reader = InputReader::open(path, args.input_format)?;
+        for row in reader.rows(args.rounding) {
+            let row = row.map_err(|e| read_error(&path.to_string_lossy(), e))?;
+            rows += 1;
+            rounded += usize::from(row.rounded);
+            if let Err(e) = row.event {
+                malformed += 1;
+                writeln!(out, "{}:{}: {e}", path.display(), row.line).map_err(AppError::Output)?;
+            }
+        }
+    }
+    writeln!(
+        out,
+        "{rows} row(s), {malformed} malformed, {rounded} rounded"
+    )
+    .map_err(AppError::Output)?;
+    out.flush().map_err(AppError::Output)?;
+
+    if malformed > 0 {
+        return Err(AppError::Parse(format!(
+            "{malformed} of {rows} row(s) are malformed"
+        )));
+    }
+    Ok(RunSummary {
+        rounded_amounts: rounded,
+        ..RunSummary::default()
+    })
+}
+
+/// Prints row counts per transaction type, distinct clients and transaction
+/// ids, across all inputs.
+fn inspect(args: &InputArgs) -> Result<RunSummary, AppError> {
+    let inputs = inputs(args)?;
+
+    let mut rows = 0usize;
+    let mut malformed = 0usize;
+    let mut rounded = 0usize;
+    let mut per_type: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut clients = HashSet::new();
+    let mut txs = HashSet::new();
+    for path in inputs {
+        let mut reader = InputReader::open(path, args.input_format)?;
+        for row in reader.rows(args.rounding) {
+            let row = row.map_err(|e| read_error(&path.to_string_lossy(), e))?;
+            rows += 1;
+            rounded += usize::from(row.rounded);
+            match row.event {
+                Ok(event) => {
+                    *per_type.entry(event.name()).or_default() += 1;
+                    clients.insert(event.client());
+                    txs.insert(event.tx());
+                }
+                Err(_) => malformed += 1,
+            }
+        }
+    }
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut report = || -> std::io::Result<()> {
+        writeln!(out, "rows: {rows}")?;
+        for (name, count) in &per_type {
+            writeln!(out, "{name}: {count}")?;
+        }
+        writeln!(out, "malformed: {malformed}")?;
+        writeln!(out, "rounded: {rounded}")?;
+        writeln!(out, "clients: {}", clients.len())?;
+        writeln!(out, "transactions: {}", txs.len())?;
+        out.flush()
+    };
+    report().map_err(AppError::Output)?;
+
+    Ok(RunSummary {
+        rounded_amounts: rounded,
+        ..RunSummary::default()
+    })
+}
+
+/// Replays a journal on top of its starting snapshot, then compares the
+/// result with `--check` and saves it to `--save-state`.
+#[cfg(feature = "serde")]
+fn recover(args: &RecoverArgs) -> Result<RunSummary, AppError> {
+    let mut ledger = match &args.load_state {
+        Some(path) => Ledger::load_snapshot(path)?,
+        None => Ledger::new(),
+    };
+    let replay = journal::read(&args.journal)?;
+
+    let events = replay.events.len();
+    let mut rejected = 0usize;
+    // Replays apply the policy the journaled run recorded.
+    let mut processor = Processor::new().with_policy(replay.policy);
+    for event in replay.events {
+        rejected += usize::from(!processor.process(&mut ledger, event)?.is_applied());
+    }
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut report = |line: String| writeln!(out, "{line}").and_then(|()| out.flush());
+    report(format!("replayed {events} event(s), {rejected} rejected")).map_err(AppError::Output)?;
+    if replay.torn_tail {
+        report("dropped an incomplete last journal entry".into()).map_err(AppError::Output)?;
+    }
+
+    if let Some(path) = &args.check {
+        let expected = Ledger::load_snapshot(path)?;
+        if ledger != expected {
+            return Err(AppError::Process(format!(
+                "recovered ledger does not match {}: {}",
+                path.display(),
+                ledger_differences(&ledger, &expected)
+            )));
+        }
+        report(format!("ledger matches {}", path.display())).map_err(AppError::Output)?;
+    }
+    if let Some(path) = &args.save_state {
+        ledger.save_snapshot(path).map_err(AppError::Output)?;
+    }
+
+    Ok(RunSummary::default())
+}
+
+/// Names the clients and transactions that differ between two ledgers.
+#[cfg(feature = "serde")]
+fn ledger_differences(actual: &Ledger, expected: &Ledger) -> String {
+    fn differing<K: Ord + Copy + std::hash::Hash, V: PartialEq>(
+        a: &std::collections::HashMap<K, V>,
+        b: &std::collections::HashMap<K, V>,
+    ) -> Vec<K> {
+        let mut keys: Vec<K> = a
+            .keys()
+            .chain(b.keys())
+            .filter(|k| a.get(k) != b.get(k))
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    format!(
+        "client(s) {:?}, transaction(s) {:?}",
+        differing(&actual.accounts, &expected.accounts),
+        differing(&actual.txs, &expected.txs)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::currency::Currency;
+
+    const MIXED_INPUT: &str = "type,client,tx,amount\n\
+deposit,1,1,2.0\n\
+deposit,oops,2,1.0\n\
+withdrawal,1,3,1.0\n\
+refund,1,4,1.0\n";
+
+    fn process_mixed(mode: ParseErrorMode) -> (Result<Vec<SkippedRow>, AppError>, Ledger, String) {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(MIXED_INPUT.as_bytes());
+        let mut ledger = Ledger::new();
+        let mut out = Vec::new();
+        let mut skipped = Vec::new();
+        let result = {
+            let mut rejects = RejectWriter::new(&mut out);
+            process_rows(
+                "mixed.csv",
+                reader::read_rows(&mut reader, RoundingPolicy::default()),
+                &mut ledger,
+                &mut Processor::new(),
+                mode,
+                Some(&mut rejects),
+                &mut skipped,
+            )
+        };
+        (
+            result.map(|_| skipped),
+            ledger,
+            String::from_utf8(out).unwrap(),
+        )
+    }
+
+    #[test]
+    fn abort_mode_stops_at_first_malformed_row() {
+        let (result, ledger, rejects) = process_mixed(ParseErrorMode::Abort);
+
+        assert!(matches!(result, Err(AppError::Parse(ref e)) if e.starts_with("mixed.csv:3:")));
+        assert_eq!(
+            ledger.accounts()[&1]
+                .balance(Currency::Unspecified)
+                .available
+                .to_string_4dp(),
+            "2.0000"
+        );
+        assert_eq!(rejects.lines().count(), 2, "header + the malformed row");
+    }
+
+    #[test]
+    fn lenient_modes_skip_malformed_rows_and_keep_going() {
+        for mode in [ParseErrorMode::Skip, ParseErrorMode::Collect] {
+            let (result, ledger, rejects) = process_mixed(mode);
+
+            let skipped = result.unwrap();
+            assert_eq!(
+                skipped.iter().map(|row| row.line).collect::<Vec<_>>(),
+                vec![3, 5]
+            );
+            assert!(skipped.iter().all(|row| row.source == "mixed.csv"));
+            assert_eq!(
+                ledger.accounts()[&1]
+                    .balance(Currency::Unspecified)
+                    .available
+                    .to_string_4dp(),
+                "1.0000"
+            );
+            assert_eq!(rejects.lines().count(), 3, "header + two malformed rows");
+        }
+    }
+
+    #[test]
+    fn process_rows_counts_rounded_amounts() {
+        let input = "type,client,tx,amount\n\
+deposit,1,1,1.00005\n\
+deposit,1,2,1.0\n\
+withdrawal,1,3,0.99999\n";
+        let mut reader = csv::ReaderBuilder::new().from_reader(input.as_bytes());
+        let mut ledger = Ledger::new();
+        let rounded = process_rows::<Vec<u8>>(
+            "rounding.csv",
+            reader::read_rows(&mut reader, RoundingPolicy::Truncate),
+            &mut ledger,
+            &mut Processor::new(),
+            ParseErrorMode::Abort,
+            None,
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(rounded, 2);
+        assert_eq!(
+            ledger.accounts()[&1]
+                .balance(Currency::Unspecified)
+                .available
+                .to_string_4dp(),
+            "1.0001"
+        );
+    }
+
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    fn json_formats_are_usage_errors_without_serde() {
+        for args in [
+            [
+                "bin",
+                "tests/fixtures/case2_input.jsonl",
+                "--output-format",
+                "csv",
+            ],
+            [
+                "bin",
+                "tests/fixtures/case2_input.csv",
+                "--output-format",
+                "json",
+            ],
+        ] {
+            let err = run(args).unwrap_err();
+            assert_eq!(err.exit_code(), exit_code::USAGE, "{err}");
+            assert!(err.to_string().contains("`serde` feature"), "{err}");
+        }
+    }
+
+    #[test]
+    fn detects_jsonl_by_extension_or_content() {
+        let detect = |path: &str, data: &[u8]| {
+            detect_format(Path::new(path), &mut BufReader::new(data)).unwrap()
+        };
+        let csv = b"type,client,tx,amount\n";
+        let jsonl = b"\n  {\"type\":\"deposit\"}\n";
+
+        assert_eq!(detect("day.jsonl", csv), InputFormat::Jsonl);
+        assert_eq!(detect("day.ndjson.gz", csv), InputFormat::Jsonl);
+        assert_eq!(detect("day.csv", jsonl), InputFormat::Csv);
+        assert_eq!(detect("-", jsonl), InputFormat::Jsonl);
+        assert_eq!(detect("-", csv), InputFormat::Csv);
+        assert_eq!(detect("day.zst", b""), InputFormat::Csv);
+    }
+
+    #[test]
+    fn stdin_can_only_be_read_once() {
+        let Command::Process(args) = Cli::try_parse_from(["bin", "-", "a.csv", "-"])
+            .unwrap()
+            .into_command()
+        else {
+            panic!("expected process");
+        };
+        assert!(matches!(inputs(&args.input), Err(AppError::Cli(_))));
+    }
+
+    #[test]
+    fn run_summary_reports_partial_success_code() {
+        assert_eq!(RunSummary::default().exit_code(), exit_code::SUCCESS);
+        assert_eq!(
+            RunSummary {
+                skipped_rows: 2,
+                ..RunSummary::default()
+            }
+            .exit_code(),
+            exit_code::PARTIAL
+        );
+    }
+}
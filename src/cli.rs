@@ -0,0 +1,432 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+#[cfg(feature = "serde")]
+use crate::io::journal::FsyncPolicy;
+use crate::{common::money::RoundingPolicy, io::compression::Compression, worker::policy::Policy};
+
+/// Applies deposits, withdrawals, disputes, resolves and chargebacks, and
+/// reports client balances.
+///
+/// Running without a subcommand is the same as `process`, so
+/// `transaction_parser <transactions.csv>` keeps working.
+#[derive(Parser, Debug)]
+#[command(
+    name = "transaction_parser",
+    version,
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub process: ProcessArgs,
+}
+
+impl Cli {
+    /// The subcommand to run; `process` when none was given.
+    pub fn into_command(self) -> Command {
+        self.command.unwrap_or(Command::Process(self.process))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Apply the transactions and write the client balances (default).
+    Process(ProcessArgs),
+    /// Parse the input and report malformed rows without applying anything.
+    Validate(InputArgs),
+    /// Print row counts per transaction type, clients and transaction ids.
+    Inspect(InputArgs),
+    /// Replay a journal on top of a snapshot, then check or save the result.
+    #[cfg(feature = "serde")]
+    Recover(RecoverArgs),
+}
+
+/// Where to read transactions from and how to parse them.
+#[derive(Args, Debug, Clone)]
+pub struct InputArgs {
+    /// Input files, processed in order into one ledger. `-` reads stdin.
+    /// Gzip and zstd input is detected and decompressed automatically.
+    #[arg(value_name = "INPUT")]
+    pub paths: Vec<PathBuf>,
+
+    /// Input file; repeat for several (alternative to the positional INPUT list).
+    #[arg(short, long = "input", value_name = "PATH", conflicts_with = "paths")]
+    pub input: Vec<PathBuf>,
+
+    /// Format of the input.
+    #[arg(long, value_enum, default_value_t)]
+    pub input_format: InputFormat,
+
+    /// What to do with amounts that have more decimals than their currency (4 without one).
+    #[arg(long, value_enum, default_value_t)]
+    pub rounding: RoundingPolicy,
+}
+
+impl InputArgs {
+    /// Inputs in the order they should be processed.
+    pub fn inputs(&self) -> &[PathBuf] {
+        if self.input.is_empty() {
+            &self.paths
+        } else {
+            &self.input
+        }
+    }
+}
+
+/// Options for the `process` command.
+#[derive(Args, Debug, Clone)]
+pub struct ProcessArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Write the balances to this file instead of stdout. The file is
+    /// replaced atomically once the report is complete.
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Format of the balances report.
+    #[arg(long, value_enum, default_value_t)]
+    pub output_format: OutputFormat,
+
+    /// Add a `status` column (active, frozen, locked or closed) to the balances report.
+    #[arg(long)]
+    pub status_column: bool,
+
+    /// Compress the balances report. Defaults to the `--output` extension
+    /// (`.gz`, `.zst`), or none.
+    #[arg(long, value_enum, value_name = "COMPRESSION")]
+    pub compress: Option<Compression>,
+
+    /// Write every skipped or invalid input row, with a reason code, to this CSV file.
+    #[arg(long, value_name = "PATH")]
+    pub rejects: Option<PathBuf>,
+
+    /// Write ledger-wide totals per currency to this CSV file.
+    #[arg(long, value_name = "PATH")]
+    pub totals: Option<PathBuf>,
+
+    /// Write the balances left with a negative available amount to this CSV file.
+    #[arg(long, value_name = "PATH")]
+    pub negative_available_report: Option<PathBuf>,
+
+    /// Start from a ledger snapshot saved by `--save-state` instead of an empty ledger.
+    #[cfg(feature = "serde")]
+    #[arg(long, value_name = "PATH")]
+    pub load_state: Option<PathBuf>,
+
+    /// Save the final ledger, including transactions and their dispute status, to this file.
+    #[cfg(feature = "serde")]
+    #[arg(long, value_name = "PATH")]
+    pub save_state: Option<PathBuf>,
+
+    /// Append every parsed event to this write-ahead journal before applying it.
+    #[cfg(feature = "serde")]
+    #[arg(long, value_name = "PATH")]
+    pub journal: Option<PathBuf>,
+
+    /// When to fsync the journal.
+    #[cfg(feature = "serde")]
+    #[arg(long, value_enum, default_value_t, requires = "journal")]
+    pub journal_fsync: FsyncPolicy,
+
+    /// What to do with rows that cannot be parsed.
+    #[arg(long, value_enum, default_value_t)]
+    pub on_parse_error: ParseErrorMode,
+
+    #[command(flatten)]
+    pub policy: Policy,
+}
+
+/// Options for the `recover` command.
+#[cfg(feature = "serde")]
+#[derive(Args, Debug, Clone)]
+pub struct RecoverArgs {
+    /// Journal written by `process --journal`.
+    #[arg(value_name = "JOURNAL")]
+    pub journal: PathBuf,
+
+    /// Snapshot the journaled run started from (its `--load-state`); an empty ledger otherwise.
+    #[arg(long, value_name = "PATH")]
+    pub load_state: Option<PathBuf>,
+
+    /// Snapshot the recovered ledger must match, such as the run's `--save-state`.
+    #[arg(long, value_name = "PATH")]
+    pub check: Option<PathBuf>,
+
+    /// Save the recovered ledger to this snapshot file.
+    #[arg(long, value_name = "PATH")]
+    pub save_state: Option<PathBuf>,
+}
+
+/// Supported input formats.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    /// JSONL for `.jsonl`/`.ndjson` files or data starting with `{`, CSV otherwise.
+    #[default]
+    Auto,
+    /// `type,client,tx,amount` CSV with a header row.
+    Csv,
+    /// JSON Lines, one `{"type":..,"client":..,"tx":..,"amount":..}` object per line.
+    Jsonl,
+}
+
+/// Supported formats for the balances report.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `client,available,held,total,locked` CSV with a header row.
+    #[default]
+    Csv,
+    /// A JSON array of `{client, available, held, total, locked}` objects.
+    Json,
+    /// JSON Lines, one `{client, available, held, total, locked}` object per line.
+    Jsonl,
+}
+
+/// What to do when an input row cannot be parsed into an event.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseErrorMode {
+    /// Stop on the first malformed row; no balances are written.
+    #[default]
+    Abort,
+    /// Skip malformed rows and report how many were skipped.
+    Skip,
+    /// Skip malformed rows and report each one with its line number.
+    Collect,
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::{
+        common::event::{EventKind, EventKinds},
+        worker::policy::{IN_FLIGHT_DISPUTES, NegativeAvailablePolicy, WithdrawalDisputePolicy},
+    };
+
+    fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+        Cli::try_parse_from(args).map(Cli::into_command)
+    }
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn bare_input_path_runs_process() {
+        let Command::Process(args) = parse(&["bin", "in.csv"]).unwrap() else {
+            panic!("expected process");
+        };
+        assert_eq!(args.input.inputs(), [PathBuf::from("in.csv")]);
+        assert_eq!(args.input.input_format, InputFormat::Auto);
+        assert_eq!(args.input.rounding, RoundingPolicy::HalfEven);
+        assert_eq!(args.on_parse_error, ParseErrorMode::Abort);
+        assert_eq!(args.output, None);
+        assert_eq!(args.rejects, None);
+        assert_eq!(args.totals, None);
+        assert_eq!(args.negative_available_report, None);
+        assert!(!args.status_column);
+        assert_eq!(args.policy, Policy::default());
+    }
+
+    #[test]
+    fn process_accepts_flags() {
+        let Command::Process(args) = parse(&[
+            "bin",
+            "process",
+            "--input",
+            "in.csv",
+            "--output=out.csv",
+            "--rejects",
+            "rej.csv",
+            "--on-parse-error=collect",
+            "--compress",
+            "zstd",
+            "--input-format",
+            "jsonl",
+            "--output-format",
+            "json",
+            "--rounding=reject",
+            "--totals",
+            "totals.csv",
+            "--negative-available-report",
+            "negative.csv",
+            "--status-column",
+            "--withdrawal-disputes=hold",
+            "--negative-available",
+            "hold-available",
+            "--redispute",
+            "--max-disputes",
+            "3",
+            "--locked-accepts",
+            "resolve",
+        ])
+        .unwrap() else {
+            panic!("expected process");
+        };
+        assert_eq!(args.input.inputs(), [PathBuf::from("in.csv")]);
+        assert_eq!(args.output, Some(PathBuf::from("out.csv")));
+        assert_eq!(args.rejects, Some(PathBuf::from("rej.csv")));
+        assert_eq!(args.on_parse_error, ParseErrorMode::Collect);
+        assert_eq!(args.output_format, OutputFormat::Json);
+        assert_eq!(args.compress, Some(Compression::Zstd));
+        assert_eq!(args.input.input_format, InputFormat::Jsonl);
+        assert_eq!(args.input.rounding, RoundingPolicy::Reject);
+        assert_eq!(args.totals, Some(PathBuf::from("totals.csv")));
+        assert_eq!(
+            args.negative_available_report,
+            Some(PathBuf::from("negative.csv"))
+        );
+        assert!(args.status_column);
+        assert_eq!(
+            args.policy.withdrawal_disputes,
+            WithdrawalDisputePolicy::Hold
+        );
+        assert_eq!(
+            args.policy.negative_available,
+            NegativeAvailablePolicy::HoldAvailable
+        );
+        assert!(args.policy.redispute);
+        assert_eq!(args.policy.max_disputes, Some(3));
+        assert_eq!(
+            args.policy.locked_accepts,
+            EventKinds::NONE.with(EventKind::Resolve)
+        );
+    }
+
+    #[test]
+    fn locked_accepts_defaults_to_in_flight_disputes() {
+        let Command::Process(args) = parse(&["bin", "in.csv"]).unwrap() else {
+            panic!("expected process");
+        };
+        assert_eq!(args.policy.locked_accepts, IN_FLIGHT_DISPUTES);
+
+        let Command::Process(args) = parse(&["bin", "in.csv", "--locked-accepts", "none"]).unwrap()
+        else {
+            panic!("expected process");
+        };
+        assert_eq!(args.policy.locked_accepts, EventKinds::NONE);
+
+        let err = parse(&["bin", "in.csv", "--locked-accepts", "resolve,refund"]).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown transaction type: refund"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn validate_and_inspect_take_an_input() {
+        assert!(matches!(
+            parse(&["bin", "validate", "in.csv"]).unwrap(),
+            Command::Validate(args) if args.inputs() == [PathBuf::from("in.csv")]
+        ));
+        assert!(matches!(
+            parse(&["bin", "inspect", "-i", "in.csv"]).unwrap(),
+            Command::Inspect(args) if args.inputs() == [PathBuf::from("in.csv")]
+        ));
+    }
+
+    #[test]
+    fn accepts_several_inputs_and_stdin_in_order() {
+        let Command::Process(args) =
+            parse(&["bin", "part-0001.csv", "-", "part-0002.csv"]).unwrap()
+        else {
+            panic!("expected process");
+        };
+        assert_eq!(
+            args.input.inputs(),
+            [
+                PathBuf::from("part-0001.csv"),
+                PathBuf::from("-"),
+                PathBuf::from("part-0002.csv")
+            ]
+        );
+
+        let Command::Validate(args) =
+            parse(&["bin", "validate", "-i", "a.csv", "-i", "b.csv"]).unwrap()
+        else {
+            panic!("expected validate");
+        };
+        assert_eq!(
+            args.inputs(),
+            [PathBuf::from("a.csv"), PathBuf::from("b.csv")]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn process_accepts_state_files() {
+        let Command::Process(args) = parse(&[
+            "bin",
+            "day2.csv",
+            "--load-state",
+            "day1.json",
+            "--save-state=day2.json",
+        ])
+        .unwrap() else {
+            panic!("expected process");
+        };
+        assert_eq!(args.load_state, Some(PathBuf::from("day1.json")));
+        assert_eq!(args.save_state, Some(PathBuf::from("day2.json")));
+        assert_eq!(args.journal, None);
+        assert_eq!(args.journal_fsync, FsyncPolicy::Always);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn journal_flags_and_recover_command() {
+        let Command::Process(args) = parse(&[
+            "bin",
+            "in.csv",
+            "--journal",
+            "run.jsonl",
+            "--journal-fsync=batch",
+        ])
+        .unwrap() else {
+            panic!("expected process");
+        };
+        assert_eq!(args.journal, Some(PathBuf::from("run.jsonl")));
+        assert_eq!(args.journal_fsync, FsyncPolicy::Batch);
+        assert!(parse(&["bin", "in.csv", "--journal-fsync=never"]).is_err());
+
+        let Command::Recover(args) = parse(&[
+            "bin",
+            "recover",
+            "run.jsonl",
+            "--load-state",
+            "day1.json",
+            "--check",
+            "day2.json",
+        ])
+        .unwrap() else {
+            panic!("expected recover");
+        };
+        assert_eq!(args.journal, PathBuf::from("run.jsonl"));
+        assert_eq!(args.load_state, Some(PathBuf::from("day1.json")));
+        assert_eq!(args.check, Some(PathBuf::from("day2.json")));
+        assert_eq!(args.save_state, None);
+        assert!(parse(&["bin", "recover"]).is_err());
+        // The policy comes from the journal header, not from flags.
+        assert!(parse(&["bin", "recover", "run.jsonl", "--redispute"]).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_values_and_conflicting_inputs() {
+        assert!(parse(&["bin", "in.csv", "--on-parse-error=ignore"]).is_err());
+        assert!(parse(&["bin", "in.csv", "--input", "other.csv"]).is_err());
+        assert!(parse(&["bin", "in.csv", "--verbose"]).is_err());
+    }
+
+    #[test]
+    fn help_and_version_are_reported_as_display_errors() {
+        let err = parse(&["bin", "--help"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::DisplayHelp);
+        let err = parse(&["bin", "--version"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::DisplayVersion);
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+
+/// Currency of an amount, which fixes how many decimal places it may have.
+///
+/// Inputs without a `currency` column use [`Currency::Unspecified`], which
+/// keeps the original 4-decimal behaviour. Every supported currency has at
+/// most 4 decimal places, so `Money` can hold any of them exactly.
+///
+/// # Examples
+/// ```
+/// use transaction_parser::common::currency::Currency;
+///
+/// let jpy: Currency = "jpy".parse().unwrap();
+/// assert_eq!(jpy, Currency::Jpy);
+/// assert_eq!(jpy.code(), "JPY");
+/// assert_eq!(jpy.decimals(), 0);
+/// assert_eq!(Currency::Usd.decimals(), 2);
+/// assert!("XYZ".parse::<Currency>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Currency {
+    /// No currency given; amounts have 4 decimal places.
+    #[default]
+    Unspecified,
+    Aud,
+    Cad,
+    Chf,
+    Cny,
+    Eur,
+    Gbp,
+    Jpy,
+    Kwd,
+    Usd,
+}
+
+impl Currency {
+    /// ISO 4217 code, or an empty string for [`Currency::Unspecified`].
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Unspecified => "",
+            Currency::Aud => "AUD",
+            Currency::Cad => "CAD",
+            Currency::Chf => "CHF",
+            Currency::Cny => "CNY",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+            Currency::Jpy => "JPY",
+            Currency::Kwd => "KWD",
+            Currency::Usd => "USD",
+        }
+    }
+
+    /// Number of decimal places in the currency's minor unit.
+    pub fn decimals(&self) -> u32 {
+        match self {
+            Currency::Unspecified => 4,
+            Currency::Kwd => 3,
+            Currency::Jpy => 0,
+            Currency::Aud
+            | Currency::Cad
+            | Currency::Chf
+            | Currency::Cny
+            | Currency::Eur
+            | Currency::Gbp
+            | Currency::Usd => 2,
+        }
+    }
+}
+
+impl std::str::FromStr for Currency {
+    type Err = String;
+
+    /// Parses an ISO 4217 code, case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_uppercase().as_str() {
+            "AUD" => Currency::Aud,
+            "CAD" => Currency::Cad,
+            "CHF" => Currency::Chf,
+            "CNY" => Currency::Cny,
+            "EUR" => Currency::Eur,
+            "GBP" => Currency::Gbp,
+            "JPY" => Currency::Jpy,
+            "KWD" => Currency::Kwd,
+            "USD" => Currency::Usd,
+            _ => return Err(format!("unknown currency {}", s.trim())),
+        })
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Serializes as the [`Currency::code`], so [`Currency::Unspecified`] is `""`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Currency {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Currency {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        if s.is_empty() {
+            return Ok(Currency::Unspecified);
+        }
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn codes_round_trip_through_from_str() {
+        for currency in [
+            Currency::Aud,
+            Currency::Cad,
+            Currency::Chf,
+            Currency::Cny,
+            Currency::Eur,
+            Currency::Gbp,
+            Currency::Jpy,
+            Currency::Kwd,
+            Currency::Usd,
+        ] {
+            assert_eq!(Currency::from_str(currency.code()), Ok(currency));
+            assert!(currency.decimals() <= Currency::Unspecified.decimals());
+        }
+    }
+
+    #[test]
+    fn from_str_ignores_case_and_whitespace() {
+        assert_eq!(Currency::from_str(" eur "), Ok(Currency::Eur));
+        assert_eq!(
+            Currency::from_str("usdollar"),
+            Err("unknown currency usdollar".to_string())
+        );
+        assert!(Currency::from_str("").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_uses_the_code() {
+        assert_eq!(serde_json::to_string(&Currency::Kwd).unwrap(), r#""KWD""#);
+        assert_eq!(
+            serde_json::to_string(&Currency::Unspecified).unwrap(),
+            r#""""#
+        );
+        assert_eq!(
+            serde_json::from_str::<Currency>(r#""""#).unwrap(),
+            Currency::Unspecified
+        );
+        assert_eq!(
+            serde_json::from_str::<Currency>(r#""eur""#).unwrap(),
+            Currency::Eur
+        );
+        assert!(serde_json::from_str::<Currency>(r#""XYZ""#).is_err());
+    }
+}
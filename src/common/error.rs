@@ -1,9 +1,37 @@
+/// Process exit codes returned by the binary.
+///
+/// | code | meaning                                                   |
+/// |------|-----------------------------------------------------------|
+/// | 0    | success                                                   |
+/// | 1    | partial success: lenient parse mode skipped malformed rows |
+/// | 2    | usage error (missing or invalid arguments)                |
+/// | 3    | input file could not be opened or read                    |
+/// | 4    | output file could not be written                          |
+/// | 5    | CSV error                                                 |
+/// | 6    | malformed input row (parse error)                         |
+/// | 7    | processing error                                          |
+pub mod exit_code {
+    pub const SUCCESS: u8 = 0;
+    pub const PARTIAL: u8 = 1;
+    pub const USAGE: u8 = 2;
+    pub const OPEN_INPUT: u8 = 3;
+    pub const OUTPUT: u8 = 4;
+    pub const CSV: u8 = 5;
+    pub const PARSE: u8 = 6;
+    pub const PROCESS: u8 = 7;
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum AppError {
-    #[error("missing input csv path. usage: cargo run -- <transactions.csv>")]
+    #[error("missing input csv path. usage: cargo run -- <transactions.csv>; see --help")]
     MissingArg,
-    #[error("failed to open input file: {0}")]
+    /// Invalid command line, or a `--help`/`--version` request.
+    #[error("{0}")]
+    Cli(#[from] clap::Error),
+    #[error("failed to open or read input file: {0}")]
     OpenInput(#[from] std::io::Error),
+    #[error("failed to write output file: {0}")]
+    Output(std::io::Error),
     #[error("csv error: {0}")]
     Csv(#[from] csv::Error),
     #[error("parse error: {0}")]
@@ -11,3 +39,52 @@ pub enum AppError {
     #[error("process error: {0}")]
     Process(String),
 }
+
+impl AppError {
+    /// Exit code the binary reports for this error; see [`exit_code`].
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            AppError::MissingArg => exit_code::USAGE,
+            // `--help` and `--version` are not failures.
+            AppError::Cli(e) if !e.use_stderr() => exit_code::SUCCESS,
+            AppError::Cli(_) => exit_code::USAGE,
+            AppError::OpenInput(_) => exit_code::OPEN_INPUT,
+            AppError::Output(_) => exit_code::OUTPUT,
+            AppError::Csv(_) => exit_code::CSV,
+            AppError::Parse(_) => exit_code::PARSE,
+            AppError::Process(_) => exit_code::PROCESS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_error_class_has_a_distinct_failure_code() {
+        let errors = [
+            AppError::MissingArg,
+            AppError::OpenInput(std::io::Error::other("x")),
+            AppError::Output(std::io::Error::other("x")),
+            AppError::Csv(csv::Error::from(std::io::Error::other("x"))),
+            AppError::Parse("x".into()),
+            AppError::Process("x".into()),
+        ];
+        let mut codes: Vec<u8> = errors.iter().map(AppError::exit_code).collect();
+
+        assert!(!codes.contains(&exit_code::SUCCESS));
+        assert!(!codes.contains(&exit_code::PARTIAL));
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+
+        let usage = clap::Error::new(clap::error::ErrorKind::UnknownArgument);
+        assert_eq!(
+            AppError::Cli(usage).exit_code(),
+            AppError::MissingArg.exit_code()
+        );
+        let help = clap::Error::new(clap::error::ErrorKind::DisplayHelp);
+        assert_eq!(AppError::Cli(help).exit_code(), exit_code::SUCCESS);
+    }
+}
@@ -1,11 +1,203 @@
-use crate::common::money::Money;
+use crate::common::{currency::Currency, money::Money};
 
 /// Represents a transaction event that is sent from the reader to the worker for processing.
+///
+/// Only deposits and withdrawals carry a currency; disputes, resolves and
+/// chargebacks use the currency of the transaction they refer to. Their
+/// optional `amount` covers part of that transaction; without it they apply
+/// to the whole amount still open for them.
 #[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
 pub enum TransactionEvent {
-    Deposit { client: u16, tx: u32, amount: Money },
-    Withdrawal { client: u16, tx: u32, amount: Money },
-    Dispute { client: u16, tx: u32 },
-    Resolve { client: u16, tx: u32 },
-    Chargeback { client: u16, tx: u32 },
+    Deposit {
+        client: u16,
+        tx: u32,
+        amount: Money,
+        currency: Currency,
+    },
+    Withdrawal {
+        client: u16,
+        tx: u32,
+        amount: Money,
+        currency: Currency,
+    },
+    Dispute {
+        client: u16,
+        tx: u32,
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        amount: Option<Money>,
+    },
+    Resolve {
+        client: u16,
+        tx: u32,
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        amount: Option<Money>,
+    },
+    Chargeback {
+        client: u16,
+        tx: u32,
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        amount: Option<Money>,
+    },
+}
+
+/// Type of a [`TransactionEvent`], without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Deposit,
+    Withdrawal,
+    Dispute,
+    Resolve,
+    Chargeback,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 5] = [
+        EventKind::Deposit,
+        EventKind::Withdrawal,
+        EventKind::Dispute,
+        EventKind::Resolve,
+        EventKind::Chargeback,
+    ];
+
+    /// Lowercase type name, as it appears in the `type` column of the input.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::Deposit => "deposit",
+            EventKind::Withdrawal => "withdrawal",
+            EventKind::Dispute => "dispute",
+            EventKind::Resolve => "resolve",
+            EventKind::Chargeback => "chargeback",
+        }
+    }
+}
+
+/// A set of event types, such as the ones a locked account still accepts.
+///
+/// Parses from and displays as a comma-separated list of type names, or
+/// `none` for the empty set.
+///
+/// # Examples
+/// ```
+/// use transaction_parser::common::event::{EventKind, EventKinds};
+///
+/// let kinds: EventKinds = "chargeback, Resolve".parse().unwrap();
+/// assert!(kinds.contains(EventKind::Resolve));
+/// assert!(!kinds.contains(EventKind::Deposit));
+/// assert_eq!(kinds.to_string(), "resolve,chargeback");
+/// assert_eq!("none".parse::<EventKinds>().unwrap(), EventKinds::NONE);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct EventKinds(u8);
+
+impl EventKinds {
+    pub const NONE: EventKinds = EventKinds(0);
+
+    /// This set plus `kind`.
+    pub const fn with(self, kind: EventKind) -> Self {
+        EventKinds(self.0 | 1 << kind as u8)
+    }
+
+    pub fn contains(&self, kind: EventKind) -> bool {
+        self.0 & 1 << kind as u8 != 0
+    }
+}
+
+impl std::str::FromStr for EventKinds {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("none") {
+            return Ok(EventKinds::NONE);
+        }
+        s.split(',').try_fold(EventKinds::NONE, |kinds, name| {
+            let name = name.trim();
+            EventKind::ALL
+                .into_iter()
+                .find(|kind| kind.name().eq_ignore_ascii_case(name))
+                .map(|kind| kinds.with(kind))
+                .ok_or_else(|| format!("unknown transaction type: {name}"))
+        })
+    }
+}
+
+impl std::fmt::Display for EventKinds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = EventKind::ALL
+            .into_iter()
+            .filter(|kind| self.contains(*kind))
+            .map(|kind| kind.name())
+            .collect();
+        if names.is_empty() {
+            f.write_str("none")
+        } else {
+            f.write_str(&names.join(","))
+        }
+    }
+}
+
+/// Same text as [`EventKinds`]' `Display`, e.g. `"resolve,chargeback"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for EventKinds {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EventKinds {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl TransactionEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            TransactionEvent::Deposit { .. } => EventKind::Deposit,
+            TransactionEvent::Withdrawal { .. } => EventKind::Withdrawal,
+            TransactionEvent::Dispute { .. } => EventKind::Dispute,
+            TransactionEvent::Resolve { .. } => EventKind::Resolve,
+            TransactionEvent::Chargeback { .. } => EventKind::Chargeback,
+        }
+    }
+
+    /// Lowercase type name, as it appears in the `type` column of the input.
+    pub fn name(&self) -> &'static str {
+        self.kind().name()
+    }
+
+    pub fn client(&self) -> u16 {
+        match self {
+            TransactionEvent::Deposit { client, .. }
+            | TransactionEvent::Withdrawal { client, .. }
+            | TransactionEvent::Dispute { client, .. }
+            | TransactionEvent::Resolve { client, .. }
+            | TransactionEvent::Chargeback { client, .. } => *client,
+        }
+    }
+
+    pub fn tx(&self) -> u32 {
+        match self {
+            TransactionEvent::Deposit { tx, .. }
+            | TransactionEvent::Withdrawal { tx, .. }
+            | TransactionEvent::Dispute { tx, .. }
+            | TransactionEvent::Resolve { tx, .. }
+            | TransactionEvent::Chargeback { tx, .. } => *tx,
+        }
+    }
 }
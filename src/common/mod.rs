@@ -1,3 +1,5 @@
+pub mod currency;
 pub mod error;
 pub mod event;
 pub mod money;
+pub mod outcome;
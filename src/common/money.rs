@@ -1,9 +1,44 @@
+//! Fixed-point money amounts.
+//!
+//! Every [`Money`] has the same scale, 10^4, whatever its currency. A
+//! currency's own scale (2 decimals for USD, 0 for JPY, ...) is applied at
+//! the edges instead: [`Money::parse_in`] rounds input to the currency's minor
+//! units and [`Money::to_string_in`] formats with its decimals. The currency
+//! itself travels next to the amount, as a `Currency` field on events and
+//! transactions and as the key of an account's balances. Every supported
+//! currency has at most 4 decimals, so one scale holds all of them exactly and
+//! amounts add and compare without conversions.
+
 use bigdecimal::BigDecimal;
 use bigdecimal::*;
 use std::cmp::Ordering;
 use std::fmt;
+use std::iter::Sum;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+use crate::common::currency::Currency;
 const SCALE: i64 = 10_000;
+// Decimal places behind `SCALE`.
+const SCALE_DIGITS: u32 = 4;
+
+/// What to do with an amount that has more decimal places than its currency
+/// allows (4 when no currency is given).
+///
+/// `Money` holds a fixed number of decimal places, so extra digits have to be
+/// dropped one way or another; the policy makes that choice explicit.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoundingPolicy {
+    /// Refuse the amount.
+    Reject,
+    /// Drop the extra digits (round toward zero).
+    Truncate,
+    /// Round to nearest, ties away from zero.
+    HalfUp,
+    /// Round to nearest, ties to even (banker's rounding).
+    #[default]
+    #[value(alias = "bankers")]
+    HalfEven,
+}
 
 #[derive(Debug, Clone, Copy, Default)]
 /// A struct representing monetary value in the smallest currency unit (e.g., cents).
@@ -14,6 +49,9 @@ const SCALE: i64 = 10_000;
 /// mixing of different numeric types. By storing money as an integer (in the smallest
 /// unit), we avoid floating-point precision issues that can occur with monetary calculations.
 ///
+/// The value counts 0.0001 units in any currency; see the module docs for how
+/// per-currency decimals are applied.
+///
 /// # Examples
 /// ```
 /// use transaction_parser::common::money::Money;
@@ -40,30 +78,321 @@ impl Money {
         self.0
     }
 
+    /// Adds `rhs`, returning `None` instead of overflowing.
+    ///
+    /// # Examples
+    /// ```
+    /// use transaction_parser::common::money::Money;
+    ///
+    /// assert_eq!(Money::new(1).checked_add(Money::new(2)), Some(Money::new(3)));
+    /// assert_eq!(Money::new(i64::MAX).checked_add(Money::new(1)), None);
+    /// ```
+    pub fn checked_add(self, rhs: Money) -> Option<Money> {
+        self.0.checked_add(rhs.0).map(Money)
+    }
+
+    /// Subtracts `rhs`, returning `None` instead of overflowing.
+    pub fn checked_sub(self, rhs: Money) -> Option<Money> {
+        self.0.checked_sub(rhs.0).map(Money)
+    }
+
     pub fn to_string_4dp(&self) -> String {
-        let bd = BigDecimal::from(self.0) / BigDecimal::from(SCALE);
-        format!("{:.4}", bd)
+        format_fixed(self.0.into(), SCALE_DIGITS)
+    }
+
+    /// Formats with the number of decimal places of `currency`, e.g. `"1.25"`
+    /// for USD and `"100"` for JPY.
+    ///
+    /// Amounts parsed with [`Money::parse_in`] are whole minor units of their
+    /// currency, so no digits are lost.
+    ///
+    /// # Examples
+    /// ```
+    /// use transaction_parser::common::{currency::Currency, money::Money};
+    ///
+    /// assert_eq!(Money::new(12_500).to_string_in(Currency::Usd), "1.25");
+    /// assert_eq!(Money::new(1_000_000).to_string_in(Currency::Jpy), "100");
+    /// assert_eq!(Money::new(12_500).to_string_in(Currency::Unspecified), "1.2500");
+    /// ```
+    pub fn to_string_in(&self, currency: Currency) -> String {
+        format_fixed(self.0.into(), currency.decimals())
+    }
+
+    /// Whether the amount is a whole number of `currency`'s minor units, i.e.
+    /// has no digits beyond its decimal places.
+    ///
+    /// # Examples
+    /// ```
+    /// use transaction_parser::common::{currency::Currency, money::Money};
+    ///
+    /// assert!(Money::new(10_100).is_whole_in(Currency::Usd));
+    /// assert!(!Money::new(10_050).is_whole_in(Currency::Usd));
+    /// assert!(!Money::new(10_100).is_whole_in(Currency::Jpy));
+    /// ```
+    pub fn is_whole_in(&self, currency: Currency) -> bool {
+        let unit = 10_i64.pow(SCALE_DIGITS - currency.decimals());
+        self.0 % unit == 0
     }
 }
 
-impl std::str::FromStr for Money {
-    type Err = ParseBigDecimalError;
+impl Money {
+    /// Parses a decimal amount, applying `policy` to digits beyond the 4th
+    /// decimal place.
+    ///
+    /// Returns the amount and whether it had to be rounded, i.e. whether the
+    /// result differs from the input.
+    ///
+    /// # Examples
+    /// ```
+    /// use transaction_parser::common::money::{Money, RoundingPolicy};
+    ///
+    /// let (m, rounded) = Money::parse_with("1.23456", RoundingPolicy::Truncate).unwrap();
+    /// assert_eq!((m.to_string_4dp().as_str(), rounded), ("1.2345", true));
+    ///
+    /// assert!(Money::parse_with("1.23456", RoundingPolicy::Reject).is_err());
+    /// assert!(Money::parse_with("1.23450", RoundingPolicy::Reject).is_ok());
+    /// ```
+    pub fn parse_with(
+        s: &str,
+        policy: RoundingPolicy,
+    ) -> Result<(Money, bool), ParseBigDecimalError> {
+        Money::parse_in(s, Currency::Unspecified, policy)
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    /// Like [`Money::parse_with`], but rounds to the decimal places of
+    /// `currency` (e.g. 2 for USD, 0 for JPY).
+    ///
+    /// # Examples
+    /// ```
+    /// use transaction_parser::common::{currency::Currency, money::{Money, RoundingPolicy}};
+    ///
+    /// let (m, rounded) = Money::parse_in("100.5", Currency::Jpy, RoundingPolicy::HalfUp).unwrap();
+    /// assert_eq!((m.to_string_in(Currency::Jpy).as_str(), rounded), ("101", true));
+    ///
+    /// assert!(Money::parse_in("1.005", Currency::Usd, RoundingPolicy::Reject).is_err());
+    /// ```
+    pub fn parse_in(
+        s: &str,
+        currency: Currency,
+        policy: RoundingPolicy,
+    ) -> Result<(Money, bool), ParseBigDecimalError> {
         let t = s.trim();
         if t.is_empty() {
             return Err(ParseBigDecimalError::Other("empty amount".into()));
         }
 
+        // Plain decimals take the integer-only fast path; anything else
+        // (exponents, `_` separators, ...) goes through BigDecimal.
+        match parse_fixed(t, currency.decimals(), policy) {
+            Some(result) => result,
+            None => reference::parse(t, currency.decimals(), policy),
+        }
+    }
+}
+
+/// Fast path of [`Money::parse_in`] for `[+-]digits[.digits]`, working on
+/// integers only. Returns `None` for any other input, which is left to
+/// [`reference::parse`].
+fn parse_fixed(
+    t: &str,
+    decimals: u32,
+    policy: RoundingPolicy,
+) -> Option<Result<(Money, bool), ParseBigDecimalError>> {
+    let (negative, unsigned) = match t.as_bytes().first()? {
+        b'-' => (true, &t[1..]),
+        b'+' => (false, &t[1..]),
+        _ => (false, t),
+    };
+    let (int, frac) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if int.is_empty() && frac.is_empty() {
+        return None;
+    }
+    if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    // Digits up to the currency's minor unit are kept; the rest only decide
+    // the rounding.
+    let (kept, dropped) = frac.split_at(frac.len().min(decimals as usize));
+    let mut units: u128 = 0;
+    for b in int.bytes().chain(kept.bytes()) {
+        units = units.checked_mul(10)?.checked_add(u128::from(b - b'0'))?;
+    }
+    units = units.checked_mul(10u128.pow(decimals - kept.len() as u32))?;
+
+    let rounded = dropped.bytes().any(|b| b != b'0');
+    // Rounding is symmetric around zero, so it works on the magnitude.
+    let round_up = match policy {
+        RoundingPolicy::Reject if rounded => {
+            return Some(Err(ParseBigDecimalError::Other(format!(
+                "amount {t} has more than {decimals} decimal places"
+            ))));
+        }
+        RoundingPolicy::Reject | RoundingPolicy::Truncate => false,
+        RoundingPolicy::HalfUp => dropped.bytes().next().is_some_and(|d| d >= b'5'),
+        RoundingPolicy::HalfEven => {
+            let mut digits = dropped.bytes();
+            match digits.next() {
+                Some(d) if d > b'5' => true,
+                Some(b'5') => digits.any(|d| d != b'0') || units % 2 == 1,
+                _ => false,
+            }
+        }
+    };
+    let units = i128::try_from(units.checked_add(u128::from(round_up))?).ok()?;
+
+    let per_minor_unit = SCALE / 10i64.pow(decimals);
+    let value = i64::try_from(if negative { -units } else { units })
+        .ok()
+        .and_then(|units| units.checked_mul(per_minor_unit));
+    Some(match value {
+        Some(value) => Ok((Money(value), rounded)),
+        None => Err(ParseBigDecimalError::Other("amount overflow".into())),
+    })
+}
+
+/// Formats a 4-decimal fixed-point value with `decimals` decimal places,
+/// rounding half-even and keeping the sign of values that round to zero, as
+/// BigDecimal's formatting does.
+fn format_fixed(value: i128, decimals: u32) -> String {
+    let step = 10u128.pow(SCALE_DIGITS - decimals);
+    let abs = value.unsigned_abs();
+    let (mut minor, rest) = (abs / step, abs % step);
+    if rest > step / 2 || (step > 1 && rest == step / 2 && minor % 2 == 1) {
+        minor += 1;
+    }
+
+    let sign = if value < 0 { "-" } else { "" };
+    let one = 10u128.pow(decimals);
+    let (int, frac) = (minor / one, minor % one);
+    if decimals == 0 {
+        format!("{sign}{int}")
+    } else {
+        format!("{sign}{int}.{frac:0width$}", width = decimals as usize)
+    }
+}
+
+/// BigDecimal implementations of parsing and formatting. They are the
+/// reference the fixed-point fast paths are checked against, and parsing
+/// still falls back to them for input the fast path does not handle.
+mod reference {
+    use super::*;
+
+    pub(super) fn parse(
+        t: &str,
+        decimals: u32,
+        policy: RoundingPolicy,
+    ) -> Result<(Money, bool), ParseBigDecimalError> {
         let bd: BigDecimal = t.parse()?;
 
-        // Scale to 4 decimal places
-        let scaled = (bd * BigDecimal::from(SCALE)).round(0);
+        // Scale to the currency's minor unit, round there, then widen to 4
+        // decimal places; every currency has at most 4.
+        let minor_units = BigDecimal::from(10i64.pow(decimals));
+        let per_minor_unit = SCALE / 10i64.pow(decimals);
+        let scaled = bd * minor_units;
+        let rounded = !scaled.is_integer();
+        let mode = match policy {
+            RoundingPolicy::Reject if rounded => {
+                return Err(ParseBigDecimalError::Other(format!(
+                    "amount {t} has more than {decimals} decimal places"
+                )));
+            }
+            RoundingPolicy::Truncate => RoundingMode::Down,
+            RoundingPolicy::HalfUp => RoundingMode::HalfUp,
+            RoundingPolicy::Reject | RoundingPolicy::HalfEven => RoundingMode::HalfEven,
+        };
         let value: i64 = scaled
+            .with_scale_round(0, mode)
             .to_i64()
+            .and_then(|units| units.checked_mul(per_minor_unit))
             .ok_or_else(|| ParseBigDecimalError::Other("amount overflow".into()))?;
 
-        Ok(Money(value))
+        Ok((Money(value), rounded))
+    }
+
+    #[cfg(test)]
+    pub(super) fn format(value: i128, decimals: u32) -> String {
+        let bd = BigDecimal::from(value) / BigDecimal::from(SCALE);
+        format!("{:.*}", decimals as usize, bd)
+    }
+}
+
+/// Sum of many [`Money`] amounts, such as ledger-wide totals.
+///
+/// Backed by `i128` at the same scale as `Money`, so adding up `i64` amounts
+/// cannot overflow in practice: that would take more than 2^63 of the largest
+/// amounts. Formats exactly like `Money`.
+///
+/// # Examples
+/// ```
+/// use transaction_parser::common::money::{Money, MoneySum};
+///
+/// let sum: MoneySum = [Money::new(i64::MAX), Money::new(i64::MAX)].into_iter().sum();
+/// assert_eq!(sum.to_string_4dp(), "1844674407370955.1614");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MoneySum(i128);
+
+impl MoneySum {
+    pub fn zero() -> Self {
+        MoneySum(0)
+    }
+
+    pub fn as_i128(&self) -> i128 {
+        self.0
+    }
+
+    pub fn to_string_4dp(&self) -> String {
+        format_fixed(self.0, SCALE_DIGITS)
+    }
+
+    /// Formats with the number of decimal places of `currency`, like
+    /// [`Money::to_string_in`].
+    pub fn to_string_in(&self, currency: Currency) -> String {
+        format_fixed(self.0, currency.decimals())
+    }
+}
+
+impl From<Money> for MoneySum {
+    fn from(m: Money) -> Self {
+        MoneySum(m.0.into())
+    }
+}
+
+impl AddAssign<Money> for MoneySum {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += i128::from(rhs.0);
+    }
+}
+
+impl Add for MoneySum {
+    type Output = MoneySum;
+    fn add(self, rhs: MoneySum) -> MoneySum {
+        MoneySum(self.0 + rhs.0)
+    }
+}
+
+impl Sum<Money> for MoneySum {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Self {
+        iter.fold(MoneySum::zero(), |mut sum, m| {
+            sum += m;
+            sum
+        })
+    }
+}
+
+impl fmt::Display for MoneySum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_4dp())
+    }
+}
+
+impl std::str::FromStr for Money {
+    type Err = ParseBigDecimalError;
+
+    /// Parses with the default [`RoundingPolicy`] (half-even).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Money::parse_with(s, RoundingPolicy::default()).map(|(money, _)| money)
     }
 }
 
@@ -86,17 +415,19 @@ impl PartialOrd for Money {
     }
 }
 
+// The operators panic on overflow in every build profile instead of wrapping
+// in release builds. Balance updates use `checked_add`/`checked_sub`.
 impl Add for Money {
     type Output = Money;
     fn add(self, rhs: Money) -> Money {
-        Money(self.0 + rhs.0)
+        self.checked_add(rhs).expect("Money addition overflowed")
     }
 }
 
 impl Sub for Money {
     type Output = Money;
     fn sub(self, rhs: Money) -> Money {
-        Money(self.0 - rhs.0)
+        self.checked_sub(rhs).expect("Money subtraction overflowed")
     }
 }
 
@@ -112,12 +443,84 @@ impl AddAssign for Money {
     }
 }
 
+/// `Money` serializes as its exact 4-decimal string (`"1.2500"`), like the
+/// balances report, and deserializes only strings that fit 4 decimals
+/// without rounding.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Money {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string_4dp())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Money {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Money::parse_with(&s, RoundingPolicy::Reject)
+            .map(|(money, _)| money)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Opt-in integer representation of [`Money`]: the raw count of 1/10000
+/// units, for use with `#[serde(with = "...")]`.
+///
+/// # Examples
+/// ```
+/// use transaction_parser::common::money::Money;
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Fee {
+///     #[serde(with = "transaction_parser::common::money::minor_units")]
+///     amount: Money,
+/// }
+///
+/// let json = serde_json::to_string(&Fee { amount: Money::new(12_500) }).unwrap();
+/// assert_eq!(json, r#"{"amount":12500}"#);
+/// let fee: Fee = serde_json::from_str(&json).unwrap();
+/// assert_eq!(fee.amount, Money::new(12_500));
+/// ```
+#[cfg(feature = "serde")]
+pub mod minor_units {
+    use super::Money;
+
+    pub fn serialize<S: serde::Serializer>(
+        money: &Money,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(money.as_i64())
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Money, D::Error> {
+        <i64 as serde::Deserialize>::deserialize(deserializer).map(Money::new)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
     use super::*;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_is_lossless_4dp_string() {
+        for raw in [0, 1, -1, 12_500, i64::MAX, i64::MIN] {
+            let money = Money(raw);
+            let json = serde_json::to_string(&money).unwrap();
+            assert_eq!(json, format!("\"{}\"", money.to_string_4dp()));
+            assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), money);
+        }
+
+        // Anything that would need rounding, or is not a string, is refused.
+        assert!(serde_json::from_str::<Money>("\"1.23456\"").is_err());
+        assert!(serde_json::from_str::<Money>("\"\"").is_err());
+        assert!(serde_json::from_str::<Money>("1.5").is_err());
+    }
+
     #[test]
     fn test_zero() {
         assert_eq!(Money::zero(), Money(0));
@@ -145,6 +548,94 @@ mod tests {
         assert_eq!(Money::from_str("0.00001").unwrap(), Money(0));
     }
 
+    #[test]
+    fn test_parse_with_rounding_policies() {
+        let parse = |s: &str, policy| Money::parse_with(s, policy).map_err(|e| e.to_string());
+
+        // (input, truncate, half-up, half-even)
+        let cases = [
+            ("1.00005", 10000, 10001, 10000),
+            ("1.00015", 10001, 10002, 10002),
+            ("1.00019", 10001, 10002, 10002),
+            ("-1.00005", -10000, -10001, -10000),
+            ("-1.99999", -19999, -20000, -20000),
+        ];
+        for (input, truncate, half_up, half_even) in cases {
+            assert_eq!(
+                parse(input, RoundingPolicy::Truncate),
+                Ok((Money(truncate), true))
+            );
+            assert_eq!(
+                parse(input, RoundingPolicy::HalfUp),
+                Ok((Money(half_up), true))
+            );
+            assert_eq!(
+                parse(input, RoundingPolicy::HalfEven),
+                Ok((Money(half_even), true))
+            );
+            assert_eq!(
+                parse(input, RoundingPolicy::Reject),
+                Err(format!("amount {input} has more than 4 decimal places"))
+            );
+        }
+
+        // Trailing zeros lose nothing, so nothing is rounded under any policy.
+        for policy in [
+            RoundingPolicy::Reject,
+            RoundingPolicy::Truncate,
+            RoundingPolicy::HalfUp,
+            RoundingPolicy::HalfEven,
+        ] {
+            assert_eq!(parse("1.250000", policy), Ok((Money(12500), false)));
+            assert_eq!(parse("3", policy), Ok((Money(30000), false)));
+        }
+    }
+
+    #[test]
+    fn test_parse_in_rounds_to_currency_decimals() {
+        let parse = |s: &str, currency, policy| {
+            Money::parse_in(s, currency, policy).map_err(|e| e.to_string())
+        };
+
+        assert_eq!(
+            parse("1.25", Currency::Usd, RoundingPolicy::Reject),
+            Ok((Money(12500), false))
+        );
+        assert_eq!(
+            parse("1.005", Currency::Usd, RoundingPolicy::HalfEven),
+            Ok((Money(10000), true))
+        );
+        assert_eq!(
+            parse("1.005", Currency::Usd, RoundingPolicy::HalfUp),
+            Ok((Money(10100), true))
+        );
+        assert_eq!(
+            parse("1.005", Currency::Usd, RoundingPolicy::Reject),
+            Err("amount 1.005 has more than 2 decimal places".to_string())
+        );
+        assert_eq!(
+            parse("99.9", Currency::Jpy, RoundingPolicy::Truncate),
+            Ok((Money(990000), true))
+        );
+        assert_eq!(
+            parse("1.2345", Currency::Kwd, RoundingPolicy::HalfEven),
+            Ok((Money(12340), true))
+        );
+        assert_eq!(
+            parse("922337203685478", Currency::Jpy, RoundingPolicy::HalfEven),
+            Err("amount overflow".to_string())
+        );
+    }
+
+    #[test]
+    fn test_to_string_in_currency() {
+        assert_eq!(Money(12500).to_string_in(Currency::Eur), "1.25");
+        assert_eq!(Money(-12500).to_string_in(Currency::Eur), "-1.25");
+        assert_eq!(Money(1000000).to_string_in(Currency::Jpy), "100");
+        assert_eq!(Money(12340).to_string_in(Currency::Kwd), "1.234");
+        assert_eq!(Money(1).to_string_in(Currency::Unspecified), "0.0001");
+    }
+
     #[test]
     fn test_from_str_invalid() {
         assert!(Money::from_str("").is_err());
@@ -178,6 +669,41 @@ mod tests {
         assert_eq!(Money(100) - Money(100), Money::zero());
     }
 
+    #[test]
+    fn test_checked_add_and_sub() {
+        assert_eq!(Money(1).checked_add(Money(2)), Some(Money(3)));
+        assert_eq!(Money(1).checked_sub(Money(2)), Some(Money(-1)));
+        assert_eq!(Money(i64::MAX).checked_add(Money(1)), None);
+        assert_eq!(Money(i64::MIN).checked_sub(Money(1)), None);
+        assert_eq!(
+            Money(-1).checked_sub(Money(i64::MAX)),
+            Some(Money(i64::MIN))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Money addition overflowed")]
+    fn test_add_panics_instead_of_wrapping() {
+        let _ = Money(i64::MAX) + Money(1);
+    }
+
+    #[test]
+    fn test_money_sum_goes_beyond_i64() {
+        let mut sum = MoneySum::zero();
+        for _ in 0..3 {
+            sum += Money(i64::MAX);
+        }
+        sum += Money(-1);
+        assert_eq!(sum.as_i128(), 3 * i128::from(i64::MAX) - 1);
+        assert_eq!(sum.to_string_4dp(), "2767011611056432.7420");
+        assert_eq!(sum.to_string_in(Currency::Jpy), "2767011611056433");
+
+        let sum: MoneySum = [Money(15000), Money(-5000)].into_iter().sum();
+        assert_eq!(sum, MoneySum::from(Money(10000)));
+        assert_eq!(sum.to_string(), "1.0000");
+        assert_eq!((sum + sum).to_string_in(Currency::Usd), "2.00");
+    }
+
     #[test]
     fn test_add_assign() {
         let mut m = Money(10000);
@@ -205,4 +731,73 @@ mod tests {
         assert_eq!(Money(10000), Money(10000));
         assert_ne!(Money(10000), Money(5000));
     }
+
+    #[test]
+    fn test_fast_path_handles_plain_decimals_only() {
+        let policy = RoundingPolicy::HalfEven;
+        assert!(parse_fixed("1.5", 4, policy).is_some());
+        assert!(parse_fixed("-.5", 2, policy).is_some());
+        assert!(parse_fixed("5.", 0, policy).is_some());
+        assert!(parse_fixed("1e3", 4, policy).is_none());
+        assert!(parse_fixed("1_000", 4, policy).is_none());
+        assert!(parse_fixed(".", 4, policy).is_none());
+        assert!(parse_fixed("-", 4, policy).is_none());
+
+        // Syntax the fast path leaves alone still parses through BigDecimal.
+        assert_eq!(
+            Money::parse_with("1e3", policy).unwrap(),
+            (Money(10_000_000), false)
+        );
+    }
+
+    const POLICIES: [RoundingPolicy; 4] = [
+        RoundingPolicy::Reject,
+        RoundingPolicy::Truncate,
+        RoundingPolicy::HalfUp,
+        RoundingPolicy::HalfEven,
+    ];
+
+    proptest::proptest! {
+        #[test]
+        fn prop_fast_parse_matches_reference(
+            // The second alternative produces ties such as `1.23450` often.
+            t in "[+-]?([0-9]{0,20}(\\.[0-9]{0,8})?|[0-9]{1,3}\\.[0-9]{0,4}50{0,3})",
+            decimals in proptest::sample::select(vec![0u32, 2, 3, 4]),
+            policy in proptest::sample::select(POLICIES.to_vec()),
+        ) {
+            let expected = reference::parse(&t, decimals, policy).map_err(|e| e.to_string());
+            match parse_fixed(&t, decimals, policy) {
+                Some(actual) => {
+                    proptest::prop_assert_eq!(actual.map_err(|e| e.to_string()), expected);
+                }
+                // Only inputs without a single digit may miss the fast path.
+                None => proptest::prop_assert!(!t.bytes().any(|b| b.is_ascii_digit())),
+            }
+        }
+
+        #[test]
+        fn prop_fast_format_matches_reference(
+            value in proptest::prelude::any::<i64>(),
+            decimals in 0u32..=4,
+        ) {
+            let value = i128::from(value);
+            proptest::prop_assert_eq!(format_fixed(value, decimals), reference::format(value, decimals));
+        }
+
+        #[test]
+        fn prop_wide_format_matches_reference(
+            value in proptest::prelude::any::<i128>(),
+            decimals in 0u32..=4,
+        ) {
+            proptest::prop_assert_eq!(format_fixed(value, decimals), reference::format(value, decimals));
+        }
+
+        #[test]
+        fn prop_small_values_format_like_reference(
+            value in -100_000i128..100_000,
+            decimals in 0u32..=4,
+        ) {
+            proptest::prop_assert_eq!(format_fixed(value, decimals), reference::format(value, decimals));
+        }
+    }
 }
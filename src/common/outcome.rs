@@ -0,0 +1,88 @@
+use crate::domain::transaction::TxStatus;
+
+/// Reason an event was skipped without changing the ledger.
+///
+/// Rejections are part of normal processing (the input is allowed to contain
+/// events that cannot be applied), so they are reported through [`Outcome`]
+/// instead of `AppError`, which is reserved for failures that stop the run.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    #[error("account is locked")]
+    AccountLocked,
+    #[error("account is frozen")]
+    AccountFrozen,
+    #[error("account is closed")]
+    AccountClosed,
+    #[error("transaction id already exists")]
+    DuplicateTx,
+    #[error("referenced transaction does not exist")]
+    TxNotFound,
+    #[error("referenced transaction belongs to another client")]
+    ClientMismatch,
+    #[error("referenced transaction is not a deposit")]
+    NotDisputable,
+    #[error("transaction status {0:?} does not allow this event")]
+    InvalidStatus(TxStatus),
+    #[error("transaction reached its dispute limit")]
+    DisputeLimit,
+    #[error("amount exceeds the open part of the referenced transaction")]
+    AmountExceeded,
+    #[error("amount has more decimal places than the referenced transaction's currency")]
+    AmountPrecision,
+    #[error("insufficient available funds")]
+    InsufficientFunds,
+    #[error("insufficient held funds")]
+    InsufficientHeldFunds,
+    #[error("balance would overflow")]
+    Overflow,
+}
+
+impl Rejection {
+    /// Stable, machine-readable reason code used in reports.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rejection::AccountLocked => "account_locked",
+            Rejection::AccountFrozen => "account_frozen",
+            Rejection::AccountClosed => "account_closed",
+            Rejection::DuplicateTx => "duplicate_tx",
+            Rejection::TxNotFound => "tx_not_found",
+            Rejection::ClientMismatch => "client_mismatch",
+            Rejection::NotDisputable => "not_disputable",
+            Rejection::InvalidStatus(_) => "invalid_status",
+            Rejection::DisputeLimit => "dispute_limit",
+            Rejection::AmountExceeded => "amount_exceeded",
+            Rejection::AmountPrecision => "amount_precision",
+            Rejection::InsufficientFunds => "insufficient_funds",
+            Rejection::InsufficientHeldFunds => "insufficient_held_funds",
+            Rejection::Overflow => "overflow",
+        }
+    }
+}
+
+/// Result of applying a single event to the ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The event changed the ledger.
+    Applied,
+    /// The event was ignored, and here is why.
+    Rejected(Rejection),
+}
+
+impl Outcome {
+    pub fn is_applied(&self) -> bool {
+        matches!(self, Outcome::Applied)
+    }
+
+    pub fn rejection(&self) -> Option<Rejection> {
+        match self {
+            Outcome::Applied => None,
+            Outcome::Rejected(r) => Some(*r),
+        }
+    }
+}
+
+impl From<Rejection> for Outcome {
+    fn from(r: Rejection) -> Self {
+        Outcome::Rejected(r)
+    }
+}
@@ -1,27 +1,237 @@
-use crate::common::money::Money;
-#[derive(Debug, Clone, Default)]
-pub struct Account {
+use std::collections::BTreeMap;
+
+use crate::common::{
+    currency::Currency,
+    event::{EventKind, EventKinds},
+    money::Money,
+    outcome::Rejection,
+};
+
+/// Funds of one client in one currency.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Balance {
     /// Funds available for normal use.
     pub available: Money,
     /// Funds held due to disputes.
     pub held: Money,
-    /// Frozen after a chargeback.
-    pub locked: bool,
+}
+
+impl Balance {
+    pub fn total(&self) -> Money {
+        self.available + self.held
+    }
+
+    /// Sets both amounts, unless their total would overflow.
+    ///
+    /// Handlers compute the new amounts with checked arithmetic and commit
+    /// them through here, and loading a snapshot checks its balances the same
+    /// way, so [`Balance::total`] does not overflow on a ledger built by either.
+    pub fn set(&mut self, available: Money, held: Money) -> Result<(), Rejection> {
+        available.checked_add(held).ok_or(Rejection::Overflow)?;
+        self.available = available;
+        self.held = held;
+        Ok(())
+    }
+}
+
+/// State of an account, which decides the events it accepts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AccountStatus {
+    /// Accepts every event.
+    #[default]
+    Active,
+    /// Manual hold: no withdrawals, while deposits and disputes go on.
+    Frozen,
+    /// Charged back: accepts only the event types the lock policy lets
+    /// through, such as resolves of disputes still open.
+    Locked,
+    /// Closed: accepts nothing.
+    Closed,
+}
+
+impl AccountStatus {
+    /// Lowercase name, as written in the `status` column.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Frozen => "frozen",
+            AccountStatus::Locked => "locked",
+            AccountStatus::Closed => "closed",
+        }
+    }
+
+    /// Whether an event of type `kind` may be applied to the account, where
+    /// a locked account accepts only `locked_accepts`.
+    pub fn allows(&self, kind: EventKind, locked_accepts: EventKinds) -> bool {
+        match self {
+            AccountStatus::Active => true,
+            AccountStatus::Frozen => kind != EventKind::Withdrawal,
+            AccountStatus::Locked => locked_accepts.contains(kind),
+            AccountStatus::Closed => false,
+        }
+    }
+
+    /// Why events this status does not allow are rejected.
+    fn rejection(&self) -> Rejection {
+        match self {
+            AccountStatus::Frozen => Rejection::AccountFrozen,
+            AccountStatus::Closed => Rejection::AccountClosed,
+            AccountStatus::Active | AccountStatus::Locked => Rejection::AccountLocked,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Account {
+    /// Balances per currency; a currency appears once it has been used.
+    pub balances: BTreeMap<Currency, Balance>,
+    /// Applies to the account as a whole, in every currency.
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "locked", deserialize_with = "status_or_locked")
+    )]
+    pub status: AccountStatus,
 }
 impl Account {
     pub fn new() -> Self {
         Self {
-            available: Money::zero(),
-            held: Money::zero(),
-            locked: false,
+            balances: BTreeMap::new(),
+            status: AccountStatus::Active,
         }
     }
 
-    pub fn total(&self) -> Money {
-        self.available + self.held
+    /// Checks that the account's status allows an event of type `kind`; see
+    /// [`AccountStatus::allows`].
+    pub fn check(&self, kind: EventKind, locked_accepts: EventKinds) -> Result<(), Rejection> {
+        if self.status.allows(kind, locked_accepts) {
+            Ok(())
+        } else {
+            Err(self.status.rejection())
+        }
+    }
+
+    /// Balance in `currency`; zero if the currency was never used.
+    pub fn balance(&self, currency: Currency) -> Balance {
+        self.balances.get(&currency).copied().unwrap_or_default()
     }
 
+    /// Mutable balance in `currency`, created at zero if needed.
+    pub fn balance_mut(&mut self, currency: Currency) -> &mut Balance {
+        self.balances.entry(currency).or_default()
+    }
+
+    /// Locked by a chargeback; this is the report's `locked` column.
     pub fn is_locked(&self) -> bool {
-        self.locked
+        self.status == AccountStatus::Locked
+    }
+}
+
+/// Reads a status, or the `locked` flag of snapshots written before
+/// accounts had one.
+#[cfg(feature = "serde")]
+fn status_or_locked<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<AccountStatus, D::Error> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Status(AccountStatus),
+        Locked(bool),
+    }
+
+    Ok(
+        match <Repr as serde::Deserialize>::deserialize(deserializer)? {
+            Repr::Status(status) => status,
+            Repr::Locked(true) => AccountStatus::Locked,
+            Repr::Locked(false) => AccountStatus::Active,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_decides_the_allowed_events() {
+        let kinds = EventKind::ALL;
+        let none = EventKinds::NONE;
+        let mut account = Account::new();
+        for kind in kinds {
+            assert_eq!(account.check(kind, none), Ok(()));
+        }
+
+        account.status = AccountStatus::Frozen;
+        for kind in kinds {
+            let expected = match kind {
+                EventKind::Withdrawal => Err(Rejection::AccountFrozen),
+                _ => Ok(()),
+            };
+            assert_eq!(account.check(kind, none), expected, "{}", kind.name());
+        }
+        assert!(!account.is_locked());
+
+        for (status, rejection) in [
+            (AccountStatus::Locked, Rejection::AccountLocked),
+            (AccountStatus::Closed, Rejection::AccountClosed),
+        ] {
+            account.status = status;
+            for kind in kinds {
+                assert_eq!(account.check(kind, none), Err(rejection));
+            }
+        }
+        assert!(!account.is_locked());
+    }
+
+    #[test]
+    fn locked_accounts_accept_only_the_allowed_event_types() {
+        let accepts = EventKinds::NONE
+            .with(EventKind::Resolve)
+            .with(EventKind::Chargeback);
+        let mut account = Account::new();
+
+        account.status = AccountStatus::Locked;
+        for kind in EventKind::ALL {
+            let expected = match kind {
+                EventKind::Resolve | EventKind::Chargeback => Ok(()),
+                _ => Err(Rejection::AccountLocked),
+            };
+            assert_eq!(account.check(kind, accepts), expected, "{}", kind.name());
+        }
+
+        // Closed accounts ignore the lock policy.
+        account.status = AccountStatus::Closed;
+        assert_eq!(
+            account.check(EventKind::Resolve, accepts),
+            Err(Rejection::AccountClosed)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn status_is_read_from_older_locked_flags() {
+        let read = |json: &str| serde_json::from_str::<Account>(json).unwrap().status;
+        assert_eq!(
+            read(r#"{"balances":{},"status":"frozen"}"#),
+            AccountStatus::Frozen
+        );
+        assert_eq!(
+            read(r#"{"balances":{},"locked":true}"#),
+            AccountStatus::Locked
+        );
+        assert_eq!(
+            read(r#"{"balances":{},"locked":false}"#),
+            AccountStatus::Active
+        );
+
+        let json = serde_json::to_string(&Account::new()).unwrap();
+        assert_eq!(json, r#"{"balances":{},"status":"active"}"#);
     }
 }
@@ -1,8 +1,39 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "serde")]
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+};
 
-use crate::domain::{account::Account, transaction::TransactionRecord};
+#[cfg(feature = "serde")]
+use crate::{common::error::AppError, domain::transaction::TxStatus, io::atomic::AtomicFile};
+use crate::{
+    common::{
+        currency::Currency,
+        money::{Money, MoneySum},
+    },
+    domain::{
+        account::{Account, Balance},
+        transaction::TransactionRecord,
+    },
+};
 
-#[derive(Debug, Default)]
+/// Balances of all accounts added up, in one currency.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub available: MoneySum,
+    pub held: MoneySum,
+}
+
+impl Totals {
+    pub fn total(&self) -> MoneySum {
+        self.available + self.held
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ledger {
     pub accounts: HashMap<u16, Account>,
     pub txs: HashMap<u32, TransactionRecord>,
@@ -21,4 +52,377 @@ impl Ledger {
     pub fn get_or_create_account(&mut self, client_id: u16) -> &mut Account {
         self.accounts.entry(client_id).or_default()
     }
+
+    /// Ledger-wide totals per currency, summed in `i128` so they cannot
+    /// overflow even when every account is near the `Money` limit.
+    pub fn totals(&self) -> BTreeMap<Currency, Totals> {
+        let mut totals: BTreeMap<Currency, Totals> = BTreeMap::new();
+        for (currency, balance) in self.accounts.values().flat_map(|acc| &acc.balances) {
+            let sum = totals.entry(*currency).or_default();
+            sum.available += balance.available;
+            sum.held += balance.held;
+        }
+        totals
+    }
+
+    /// Balances left with a negative `available` amount, by client and
+    /// currency. Disputes of funds already withdrawn cause these under the
+    /// default policy.
+    pub fn negative_available(&self) -> BTreeMap<(u16, Currency), Balance> {
+        self.accounts
+            .iter()
+            .flat_map(|(client, acc)| {
+                acc.balances
+                    .iter()
+                    .map(move |(currency, balance)| ((*client, *currency), *balance))
+            })
+            .filter(|(_, balance)| balance.available < Money::zero())
+            .collect()
+    }
+}
+
+/// Version written into every snapshot. [`Ledger::load_snapshot`] also reads
+/// version 1, from before transaction records tracked partial amounts, and
+/// refuses any other.
+#[cfg(feature = "serde")]
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct SnapshotOut<'a> {
+    version: u32,
+    ledger: &'a Ledger,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SnapshotIn {
+    version: u32,
+    // Decoded only once the version is known to match.
+    ledger: serde_json::Value,
+}
+
+#[cfg(feature = "serde")]
+impl Ledger {
+    /// Saves the whole ledger, accounts and transaction records with their
+    /// dispute status, as a JSON snapshot.
+    ///
+    /// The file is replaced atomically, so an interrupted save leaves the
+    /// previous snapshot in place.
+    ///
+    /// # Examples
+    /// ```
+    /// use transaction_parser::common::{currency::Currency, money::Money};
+    /// use transaction_parser::domain::ledger::Ledger;
+    ///
+    /// let path = std::env::temp_dir().join(format!("ledger_doc_{}.json", std::process::id()));
+    ///
+    /// let mut ledger = Ledger::new();
+    /// ledger.get_or_create_account(1).balance_mut(Currency::Unspecified).available = Money::new(5);
+    /// ledger.save_snapshot(&path).unwrap();
+    ///
+    /// let restored = Ledger::load_snapshot(&path).unwrap();
+    /// assert_eq!(restored.accounts()[&1].balance(Currency::Unspecified).available, Money::new(5));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_snapshot(&self, path: &Path) -> io::Result<()> {
+        let mut out = AtomicFile::create(path)?;
+        let snapshot = SnapshotOut {
+            version: SNAPSHOT_VERSION,
+            ledger: self,
+        };
+        serde_json::to_writer(&mut out, &snapshot)?;
+        out.commit()
+    }
+
+    /// Loads a ledger written by [`Ledger::save_snapshot`].
+    ///
+    /// A file that cannot be read or decoded fails with
+    /// [`AppError::OpenInput`]; one whose balances or transaction records
+    /// break the invariants the handlers keep fails with [`AppError::Parse`].
+    pub fn load_snapshot(path: &Path) -> Result<Ledger, AppError> {
+        let with_path = |e: io::Error| io::Error::new(e.kind(), format!("{}: {e}", path.display()));
+
+        let file = File::open(path).map_err(with_path)?;
+        let snapshot: SnapshotIn =
+            serde_json::from_reader(BufReader::new(file)).map_err(|e| with_path(e.into()))?;
+        if !(1..=SNAPSHOT_VERSION).contains(&snapshot.version) {
+            return Err(with_path(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported snapshot version {} (expected 1 to {SNAPSHOT_VERSION})",
+                    snapshot.version
+                ),
+            ))
+            .into());
+        }
+        let mut ledger: Ledger =
+            serde_json::from_value(snapshot.ledger).map_err(|e| with_path(e.into()))?;
+        if snapshot.version == 1 {
+            ledger.upgrade_from_v1();
+        }
+        ledger
+            .check_invariants()
+            .map_err(|msg| AppError::Parse(format!("{}: {msg}", path.display())))?;
+        Ok(ledger)
+    }
+
+    // The handlers only commit balances through `Balance::set` and keep the
+    // disputed and charged-back parts of a record within its amount, so
+    // `Balance::total` and `TransactionRecord::disputable` cannot overflow.
+    // A snapshot edited by hand may not, and is refused instead. `resolved`
+    // is left out: funds resolved and disputed again count once per cycle.
+    fn check_invariants(&self) -> Result<(), String> {
+        for (client, acc) in &self.accounts {
+            for (currency, balance) in &acc.balances {
+                Balance::default()
+                    .set(balance.available, balance.held)
+                    .map_err(|_| {
+                        format!("client {client}: available + held overflows in {currency:?}")
+                    })?;
+            }
+        }
+        for (tx_id, tx) in &self.txs {
+            let zero = Money::zero();
+            let within_amount = tx.disputed >= zero
+                && tx.charged_back >= zero
+                && tx
+                    .disputed
+                    .checked_add(tx.charged_back)
+                    .is_some_and(|parts| parts <= tx.amount);
+            if !within_amount {
+                return Err(format!(
+                    "tx {tx_id}: disputed and charged-back parts exceed the amount"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Version 1 records only moved their whole amount, so their status tells
+    // which part is disputed, resolved or charged back.
+    fn upgrade_from_v1(&mut self) {
+        for tx in self.txs.values_mut() {
+            match tx.tx_status {
+                TxStatus::Normal => {}
+                TxStatus::Disputed => tx.disputed = tx.amount,
+                TxStatus::Resolved => tx.resolved = tx.amount,
+                TxStatus::ChargedBack => tx.charged_back = tx.amount,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_add_up_every_account_per_currency_without_overflow() {
+        let mut ledger = Ledger::new();
+        for client in 1..=3 {
+            let usd = ledger
+                .get_or_create_account(client)
+                .balance_mut(Currency::Usd);
+            usd.available = Money::new(i64::MAX);
+            usd.held = Money::new(10_000);
+        }
+        ledger
+            .get_or_create_account(4)
+            .balance_mut(Currency::Eur)
+            .available = Money::new(-25_000);
+
+        let totals = ledger.totals();
+        assert_eq!(totals.len(), 2);
+
+        let usd = totals[&Currency::Usd];
+        assert_eq!(usd.available.as_i128(), 3 * i128::from(i64::MAX));
+        assert_eq!(usd.held.to_string_in(Currency::Usd), "3.00");
+        assert_eq!(usd.total().as_i128(), 3 * i128::from(i64::MAX) + 30_000);
+        assert_eq!(
+            totals[&Currency::Eur].total().to_string_in(Currency::Eur),
+            "-2.50"
+        );
+    }
+
+    #[test]
+    fn negative_available_lists_overdrawn_balances_in_order() {
+        let mut ledger = Ledger::new();
+        for (client, currency, available) in [
+            (2, Currency::Usd, -1),
+            (1, Currency::Eur, 5),
+            (1, Currency::Usd, -3),
+            (3, Currency::Usd, 0),
+        ] {
+            ledger
+                .get_or_create_account(client)
+                .balance_mut(currency)
+                .available = Money::new(available);
+        }
+
+        let negative = ledger.negative_available();
+        let keys: Vec<_> = negative.keys().copied().collect();
+        assert_eq!(keys, vec![(1, Currency::Usd), (2, Currency::Usd)]);
+        assert_eq!(negative[&(1, Currency::Usd)].available, Money::new(-3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips_accounts_and_transactions() {
+        use crate::domain::{
+            account::AccountStatus,
+            transaction::{TxStatus, TxType},
+        };
+
+        let mut ledger = Ledger::new();
+        let account = ledger.get_or_create_account(7);
+        account.balance_mut(Currency::Usd).held = Money::new(12_500);
+        account.status = AccountStatus::Locked;
+        let mut tx =
+            TransactionRecord::new(3, 7, Money::new(12_500), TxType::Deposit, TxStatus::Normal);
+        tx.currency = Currency::Usd;
+        tx.set_status(TxStatus::Disputed);
+        tx.disputed = Money::new(10_000);
+        tx.resolved = Money::new(2_500);
+        ledger.txs.insert(3, tx);
+
+        let json = serde_json::to_string(&ledger).unwrap();
+        assert!(
+            json.contains(r#""amount":"1.2500","currency":"USD""#),
+            "{json}"
+        );
+        assert!(
+            json.contains(r#""tx_type":"deposit","tx_status":"disputed""#),
+            "{json}"
+        );
+
+        let restored: Ledger = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, ledger);
+        let account = &restored.accounts[&7];
+        assert!(account.is_locked());
+        assert_eq!(account.balance(Currency::Usd).held, Money::new(12_500));
+        let tx = &restored.txs[&3];
+        assert_eq!(
+            (tx.client, tx.amount, tx.currency),
+            (7, Money::new(12_500), Currency::Usd)
+        );
+        assert_eq!(tx.tx_status, TxStatus::Disputed);
+        assert_eq!(
+            (tx.disputed, tx.resolved),
+            (Money::new(10_000), Money::new(2_500))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn load_snapshot_upgrades_version_1_records_from_their_status() {
+        let path = std::env::temp_dir().join(format!(
+            "transaction_parser_snapshot_v1_{}.json",
+            std::process::id()
+        ));
+        let record = |tx: u32, status: &str| {
+            format!(
+                r#""{tx}":{{"tx_id":{tx},"client":1,"amount":"2.0000","currency":"","tx_type":"deposit","tx_status":"{status}"}}"#
+            )
+        };
+        let txs = [
+            record(1, "normal"),
+            record(2, "disputed"),
+            record(3, "resolved"),
+            record(4, "charged_back"),
+        ]
+        .join(",");
+        std::fs::write(
+            &path,
+            format!(r#"{{"version":1,"ledger":{{"accounts":{{}},"txs":{{{txs}}}}}}}"#),
+        )
+        .unwrap();
+        let ledger = Ledger::load_snapshot(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let parts = |tx: u32| {
+            let t = &ledger.txs[&tx];
+            (t.disputed, t.resolved, t.charged_back)
+        };
+        let (zero, two) = (Money::zero(), Money::new(20_000));
+        assert_eq!(parts(1), (zero, zero, zero));
+        assert_eq!(parts(2), (two, zero, zero));
+        assert_eq!(parts(3), (zero, two, zero));
+        assert_eq!(parts(4), (zero, zero, two));
+        assert_eq!(ledger.txs[&2].tx_status, TxStatus::Disputed);
+        assert_eq!(ledger.txs[&2].disputable(), zero);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn load_snapshot_rejects_other_versions_and_names_the_file() {
+        let path = std::env::temp_dir().join(format!(
+            "transaction_parser_snapshot_version_{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, r#"{"version":99,"ledger":{}}"#).unwrap();
+        let err = Ledger::load_snapshot(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        let AppError::OpenInput(err) = err else {
+            panic!("{err:?}");
+        };
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let msg = err.to_string();
+        assert!(msg.contains("unsupported snapshot version 99"), "{msg}");
+        assert!(msg.contains(&path.display().to_string()), "{msg}");
+
+        let err = Ledger::load_snapshot(&path).unwrap_err();
+        assert!(
+            matches!(&err, AppError::OpenInput(e) if e.kind() == io::ErrorKind::NotFound),
+            "{err:?}"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn load_snapshot_rejects_balances_and_records_that_would_overflow() {
+        let path = std::env::temp_dir().join(format!(
+            "transaction_parser_snapshot_invariants_{}.json",
+            std::process::id()
+        ));
+        let load = |ledger: &str| {
+            std::fs::write(&path, format!(r#"{{"version":2,"ledger":{ledger}}}"#)).unwrap();
+            Ledger::load_snapshot(&path)
+        };
+        let max = Money::new(i64::MAX).to_string();
+        let record = |amount: &str, disputed: &str, charged_back: &str| {
+            format!(
+                r#"{{"accounts":{{}},"txs":{{"5":{{"tx_id":5,"client":1,"amount":"{amount}","currency":"","tx_type":"deposit","tx_status":"disputed","disputed":"{disputed}","charged_back":"{charged_back}"}}}}}}"#
+            )
+        };
+
+        let overflowing = format!(
+            r#"{{"accounts":{{"3":{{"balances":{{"USD":{{"available":"{max}","held":"0.0001"}}}},"status":"active"}}}},"txs":{{}}}}"#
+        );
+        let cases = [
+            (overflowing, "client 3: available + held overflows"),
+            (
+                record("2.0", "1.5", "1.0"),
+                "tx 5: disputed and charged-back",
+            ),
+            (
+                record("2.0", "-1.0", "0"),
+                "tx 5: disputed and charged-back",
+            ),
+            (record(&max, &max, &max), "tx 5: disputed and charged-back"),
+        ];
+        for (ledger, expected) in cases {
+            let err = load(&ledger).unwrap_err();
+            let AppError::Parse(msg) = &err else {
+                panic!("{err:?}");
+            };
+            assert!(msg.contains(expected), "{msg}");
+            assert!(msg.contains(&path.display().to_string()), "{msg}");
+        }
+
+        let ledger = load(&record("2.0", "1.5", "0.5")).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(ledger.txs[&5].disputable(), Money::zero());
+    }
 }
@@ -1,19 +1,44 @@
-use crate::common::money::Money;
+use crate::common::{currency::Currency, event::EventKind, money::Money, outcome::Rejection};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TransactionRecord {
     pub tx_id: u32,
     pub client: u16,
     pub amount: Money,
+    /// Currency of `amount`; disputes move funds in this currency.
+    pub currency: Currency,
     pub tx_type: TxType,
     pub tx_status: TxStatus,
+    /// Disputes opened so far, counted against [`TxStateMachine`]'s limit.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub disputes: u32,
+    /// Part of `amount` under the open dispute, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub disputed: Money,
+    /// Part of `amount` resolved so far, over every dispute cycle.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub resolved: Money,
+    /// Part of `amount` charged back so far.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub charged_back: Money,
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum TxType {
     Deposit,
     Withdrawal,
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum TxStatus {
     Normal,
     Disputed,
@@ -22,6 +47,7 @@ pub enum TxStatus {
 }
 
 impl TransactionRecord {
+    /// Creates a record without a currency.
     pub fn new(
         tx_id: u32,
         client: u16,
@@ -33,12 +59,275 @@ impl TransactionRecord {
             tx_id,
             client,
             amount,
+            currency: Currency::Unspecified,
             tx_type,
             tx_status,
+            disputes: 0,
+            disputed: Money::zero(),
+            resolved: Money::zero(),
+            charged_back: Money::zero(),
         }
     }
 
+
     pub fn set_status(&mut self, status: TxStatus) {
         self.tx_status = status;
     }
+
+    /// Part of `amount` a dispute may still cover: neither under dispute nor
+    /// charged back.
+    pub fn disputable(&self) -> Money {
+        self.amount - self.disputed - self.charged_back
+    }
+}
+
+/// Amount a dispute, resolve or chargeback moves: `requested`, or all of
+/// `open` without a request. Never more than `open`, so the disputed,
+/// resolved and charged-back parts cannot add up past the original amount,
+/// and always whole minor units of the transaction's `currency`, so the
+/// parts can be reported in it without rounding.
+///
+/// # Examples
+/// ```
+/// use transaction_parser::common::{currency::Currency, money::Money, outcome::Rejection};
+/// use transaction_parser::domain::transaction::partial_amount;
+///
+/// let open = Money::new(50_000);
+/// let usd = Currency::Usd;
+/// assert_eq!(partial_amount(None, open, usd), Ok(open));
+/// assert_eq!(partial_amount(Some(Money::new(20_000)), open, usd), Ok(Money::new(20_000)));
+/// assert_eq!(
+///     partial_amount(Some(Money::new(60_000)), open, usd),
+///     Err(Rejection::AmountExceeded)
+/// );
+/// assert_eq!(
+///     partial_amount(Some(Money::new(20_050)), open, usd),
+///     Err(Rejection::AmountPrecision)
+/// );
+/// ```
+pub fn partial_amount(
+    requested: Option<Money>,
+    open: Money,
+    currency: Currency,
+) -> Result<Money, Rejection> {
+    let amount = requested.unwrap_or(open);
+    if !amount.is_whole_in(currency) {
+        return Err(Rejection::AmountPrecision);
+    }
+    if amount > open || amount <= Money::zero() {
+        return Err(Rejection::AmountExceeded);
+    }
+    Ok(amount)
+}
+
+/// A status change that a dispute, resolve or chargeback may make.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub from: TxStatus,
+    pub event: EventKind,
+    pub to: TxStatus,
+}
+
+impl Transition {
+    pub const fn new(from: TxStatus, event: EventKind, to: TxStatus) -> Self {
+        Self { from, event, to }
+    }
+}
+
+/// `Normal -> Disputed -> Resolved | ChargedBack`.
+pub const STANDARD_TRANSITIONS: [Transition; 3] = [
+    Transition::new(TxStatus::Normal, EventKind::Dispute, TxStatus::Disputed),
+    Transition::new(TxStatus::Disputed, EventKind::Resolve, TxStatus::Resolved),
+    Transition::new(
+        TxStatus::Disputed,
+        EventKind::Chargeback,
+        TxStatus::ChargedBack,
+    ),
+];
+
+/// `Resolved -> Disputed`: a resolved transaction is disputed again, as in a
+/// second presentment or pre-arbitration cycle.
+pub const REDISPUTE: Transition =
+    Transition::new(TxStatus::Resolved, EventKind::Dispute, TxStatus::Disputed);
+
+/// Table of the status changes transactions may go through.
+///
+/// An event is allowed on a transaction only if the table has a transition
+/// from its status for that event. Disputes can also be capped per
+/// transaction, which bounds the cycles [`REDISPUTE`] allows.
+///
+/// # Examples
+/// ```
+/// use transaction_parser::common::{event::EventKind, money::Money, outcome::Rejection};
+/// use transaction_parser::domain::transaction::{
+///     REDISPUTE, TransactionRecord, TxStateMachine, TxStatus, TxType,
+/// };
+///
+/// let mut tx = TransactionRecord::new(1, 1, Money::new(1), TxType::Deposit, TxStatus::Resolved);
+/// tx.disputes = 1;
+///
+/// let standard = TxStateMachine::default();
+/// assert_eq!(
+///     standard.next(&tx, EventKind::Dispute),
+///     Err(Rejection::InvalidStatus(TxStatus::Resolved))
+/// );
+///
+/// let cycles = TxStateMachine::default().with_transition(REDISPUTE);
+/// assert_eq!(cycles.next(&tx, EventKind::Dispute), Ok(TxStatus::Disputed));
+/// assert_eq!(
+///     cycles.with_max_disputes(1).next(&tx, EventKind::Dispute),
+///     Err(Rejection::DisputeLimit)
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxStateMachine {
+    transitions: Vec<Transition>,
+    max_disputes: Option<u32>,
+}
+
+impl Default for TxStateMachine {
+    /// [`STANDARD_TRANSITIONS`], without a dispute limit.
+    fn default() -> Self {
+        Self {
+            transitions: STANDARD_TRANSITIONS.to_vec(),
+            max_disputes: None,
+        }
+    }
+}
+
+impl TxStateMachine {
+    /// Adds `transition` to the table. A transition must end in the status
+    /// its event stands for, as the event decides how balances move.
+    pub fn with_transition(mut self, transition: Transition) -> Self {
+        debug_assert_eq!(Some(transition.to), Self::target(transition.event));
+        if !self.transitions.contains(&transition) {
+            self.transitions.push(transition);
+        }
+        self
+    }
+
+    /// Lets each transaction be disputed at most `max` times.
+    pub fn with_max_disputes(mut self, max: u32) -> Self {
+        self.max_disputes = Some(max);
+        self
+    }
+
+    /// Status `record` moves to on `event`, or why the event is not allowed.
+    pub fn next(
+        &self,
+        record: &TransactionRecord,
+        event: EventKind,
+    ) -> Result<TxStatus, Rejection> {
+        let transition = self
+            .transitions
+            .iter()
+            .find(|t| t.from == record.tx_status && t.event == event)
+            .ok_or(Rejection::InvalidStatus(record.tx_status))?;
+        if event == EventKind::Dispute
+            && self.max_disputes.is_some_and(|max| record.disputes >= max)
+        {
+            return Err(Rejection::DisputeLimit);
+        }
+        Ok(transition.to)
+    }
+
+    fn target(event: EventKind) -> Option<TxStatus> {
+        match event {
+            EventKind::Dispute => Some(TxStatus::Disputed),
+            EventKind::Resolve => Some(TxStatus::Resolved),
+            EventKind::Chargeback => Some(TxStatus::ChargedBack),
+            EventKind::Deposit | EventKind::Withdrawal => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(status: TxStatus, disputes: u32) -> TransactionRecord {
+        let mut tx = TransactionRecord::new(1, 1, Money::new(1), TxType::Deposit, status);
+        tx.disputes = disputes;
+        tx
+    }
+
+    #[test]
+    fn standard_table_allows_a_single_dispute_cycle() {
+        let machine = TxStateMachine::default();
+        let cases = [
+            (TxStatus::Normal, EventKind::Dispute, Ok(TxStatus::Disputed)),
+            (TxStatus::Normal, EventKind::Resolve, Err(TxStatus::Normal)),
+            (
+                TxStatus::Normal,
+                EventKind::Chargeback,
+                Err(TxStatus::Normal),
+            ),
+            (
+                TxStatus::Disputed,
+                EventKind::Dispute,
+                Err(TxStatus::Disputed),
+            ),
+            (
+                TxStatus::Disputed,
+                EventKind::Resolve,
+                Ok(TxStatus::Resolved),
+            ),
+            (
+                TxStatus::Disputed,
+                EventKind::Chargeback,
+                Ok(TxStatus::ChargedBack),
+            ),
+            (
+                TxStatus::Resolved,
+                EventKind::Dispute,
+                Err(TxStatus::Resolved),
+            ),
+            (
+                TxStatus::ChargedBack,
+                EventKind::Dispute,
+                Err(TxStatus::ChargedBack),
+            ),
+            (
+                TxStatus::ChargedBack,
+                EventKind::Resolve,
+                Err(TxStatus::ChargedBack),
+            ),
+        ];
+        for (from, event, expected) in cases {
+            assert_eq!(
+                machine.next(&record(from, 0), event),
+                expected.map_err(Rejection::InvalidStatus),
+                "{from:?} on {}",
+                event.name()
+            );
+        }
+    }
+
+    #[test]
+    fn redispute_cycles_are_capped_per_transaction() {
+        let machine = TxStateMachine::default()
+            .with_transition(REDISPUTE)
+            .with_transition(REDISPUTE)
+            .with_max_disputes(2);
+        assert_eq!(machine.transitions.len(), STANDARD_TRANSITIONS.len() + 1);
+
+        assert_eq!(
+            machine.next(&record(TxStatus::Resolved, 1), EventKind::Dispute),
+            Ok(TxStatus::Disputed)
+        );
+        assert_eq!(
+            machine.next(&record(TxStatus::Resolved, 2), EventKind::Dispute),
+            Err(Rejection::DisputeLimit)
+        );
+        // The limit only applies to disputes.
+        assert_eq!(
+            machine.next(&record(TxStatus::Disputed, 2), EventKind::Resolve),
+            Ok(TxStatus::Resolved)
+        );
+        // A charged-back transaction stays final.
+        assert_eq!(
+            machine.next(&record(TxStatus::ChargedBack, 1), EventKind::Dispute),
+            Err(Rejection::InvalidStatus(TxStatus::ChargedBack))
+        );
+    }
 }
@@ -0,0 +1,209 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// A file that only appears at its final path once it is complete.
+///
+/// Writes go to a temporary file in the same directory as the target, so the
+/// final rename never crosses a filesystem. [`AtomicFile::commit`] flushes and
+/// fsyncs the temporary file and renames it over the target; readers see
+/// either the previous file or the complete new one, never a partial write.
+/// Dropping an `AtomicFile` without committing removes the temporary file.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use transaction_parser::io::atomic::AtomicFile;
+///
+/// let path = std::env::temp_dir().join(format!("atomic_doc_{}.csv", std::process::id()));
+///
+/// let mut out = AtomicFile::create(&path).unwrap();
+/// out.write_all(b"client,available,held,total,locked\n").unwrap();
+/// assert!(!path.exists());
+///
+/// out.commit().unwrap();
+/// assert!(path.exists());
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct AtomicFile {
+    path: PathBuf,
+    tmp_path: PathBuf,
+    // `None` once committed.
+    file: Option<BufWriter<File>>,
+}
+
+impl AtomicFile {
+    /// Creates a temporary file next to `path`.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let dir = parent_dir(path);
+        let name = path.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}: not a file path", path.display()),
+            )
+        })?;
+
+        // `create_new` never clobbers a temp file owned by a concurrent run.
+        let mut attempt = 0u32;
+        loop {
+            let mut tmp_name = OsString::from(".");
+            tmp_name.push(name);
+            tmp_name.push(format!(".tmp.{}.{attempt}", std::process::id()));
+            let tmp_path = dir.join(tmp_name);
+
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&tmp_path)
+            {
+                Ok(file) => {
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                        tmp_path,
+                        file: Some(BufWriter::new(file)),
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => {
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!("{}: {e}", tmp_path.display()),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Flushes and fsyncs the data, then renames it into place.
+    pub fn commit(mut self) -> io::Result<()> {
+        let file = self.file.take().expect("file is present until commit");
+        let file = file.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&self.tmp_path, &self.path)?;
+        sync_dir(parent_dir(&self.path))
+    }
+
+    fn file(&mut self) -> &mut BufWriter<File> {
+        self.file.as_mut().expect("file is present until commit")
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file().flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.file.take().is_some() {
+            // Not committed: leave the previous file untouched.
+            let _ = fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+pub(crate) fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+// Persist the rename itself; directories cannot be opened for sync on Windows.
+#[cfg(unix)]
+pub(crate) fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Helper: a fresh, empty directory under the system temp dir.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "transaction_parser_atomic_{name}_{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn commit_replaces_the_target_and_leaves_no_temp_file() {
+        let dir = temp_dir("commit");
+        let path = dir.join("out.csv");
+        fs::write(&path, "old").unwrap();
+
+        let mut out = AtomicFile::create(&path).unwrap();
+        out.write_all(b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+
+        out.commit().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(entries(&dir), vec!["out.csv"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dropping_without_commit_keeps_the_previous_file() {
+        let dir = temp_dir("drop");
+        let path = dir.join("out.csv");
+        fs::write(&path, "old").unwrap();
+
+        {
+            let mut out = AtomicFile::create(&path).unwrap();
+            out.write_all(b"half a rep").unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        assert_eq!(entries(&dir), vec!["out.csv"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_writers_get_distinct_temp_files() {
+        let dir = temp_dir("concurrent");
+        let path = dir.join("out.csv");
+
+        let first = AtomicFile::create(&path).unwrap();
+        let second = AtomicFile::create(&path).unwrap();
+        assert_ne!(first.tmp_path, second.tmp_path);
+
+        drop(first);
+        drop(second);
+        assert!(entries(&dir).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    io::{self, Cursor, Read, Write},
+    path::Path,
+};
+
+use flate2::{read::MultiGzDecoder, write::GzEncoder};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression of an input or output stream.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain, uncompressed data.
+    #[default]
+    None,
+    /// gzip (`.gz`).
+    Gzip,
+    /// Zstandard (`.zst`).
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression from the first bytes of a stream.
+    pub fn from_magic(prefix: &[u8]) -> Self {
+        if prefix.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else if prefix.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Picks the compression for a file name ending in `.gz` or `.zst`.
+    pub fn from_extension(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Wraps `reader` so compressed input is decompressed transparently.
+///
+/// The compression is detected from the magic bytes at the start of the
+/// stream, so it works for stdin as well as for files with any extension.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Read, Write};
+/// use transaction_parser::io::compression::{decoder, Compression, Encoder};
+///
+/// let mut gz = Encoder::new(Vec::new(), Compression::Gzip).unwrap();
+/// gz.write_all(b"type,client,tx,amount\n").unwrap();
+/// let compressed = gz.finish().unwrap();
+///
+/// let mut plain = String::new();
+/// decoder(compressed.as_slice()).unwrap().read_to_string(&mut plain).unwrap();
+/// assert_eq!(plain, "type,client,tx,amount\n");
+/// ```
+pub fn decoder<'a, R: Read + 'a>(mut reader: R) -> io::Result<Box<dyn Read + 'a>> {
+    // Read the magic bytes up front; a pipe may hand them over in pieces.
+    let mut prefix = [0u8; ZSTD_MAGIC.len()];
+    let mut len = 0;
+    while len < prefix.len() {
+        match reader.read(&mut prefix[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let compression = Compression::from_magic(&prefix[..len]);
+    let stream = Cursor::new(prefix).take(len as u64).chain(reader);
+
+    Ok(match compression {
+        Compression::None => Box::new(stream),
+        // Concatenated gzip members (e.g. `cat a.gz b.gz`) read as one stream.
+        Compression::Gzip => Box::new(MultiGzDecoder::new(stream)),
+        Compression::Zstd => Box::new(zstd::Decoder::new(stream)?),
+    })
+}
+
+/// Writer that compresses everything written to it.
+///
+/// [`Encoder::finish`] must be called to write the compression trailer; it
+/// hands back the inner writer.
+pub enum Encoder<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W, compression: Compression) -> io::Result<Self> {
+        Ok(match compression {
+            Compression::None => Encoder::Plain(writer),
+            Compression::Gzip => {
+                Encoder::Gzip(GzEncoder::new(writer, flate2::Compression::default()))
+            }
+            Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(writer, 0)?),
+        })
+    }
+
+    /// Writes any pending compressed data and returns the inner writer.
+    pub fn finish(self) -> io::Result<W> {
+        match self {
+            Encoder::Plain(mut w) => {
+                w.flush()?;
+                Ok(w)
+            }
+            Encoder::Gzip(w) => w.finish(),
+            Encoder::Zstd(w) => w.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Plain(w) => w.write(buf),
+            Encoder::Gzip(w) => w.write(buf),
+            Encoder::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Plain(w) => w.flush(),
+            Encoder::Gzip(w) => w.flush(),
+            Encoder::Zstd(w) => w.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "type,client,tx,amount\ndeposit,1,1,1.0\n";
+
+    fn compress(compression: Compression) -> Vec<u8> {
+        let mut enc = Encoder::new(Vec::new(), compression).unwrap();
+        enc.write_all(CSV.as_bytes()).unwrap();
+        enc.finish().unwrap()
+    }
+
+    fn decompress(bytes: &[u8]) -> String {
+        let mut out = String::new();
+        decoder(bytes).unwrap().read_to_string(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn round_trips_every_compression() {
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            let bytes = compress(compression);
+            assert_eq!(Compression::from_magic(&bytes), compression);
+            assert_eq!(decompress(&bytes), CSV, "{compression:?}");
+        }
+    }
+
+    #[test]
+    fn reads_concatenated_gzip_members() {
+        let mut bytes = compress(Compression::Gzip);
+        bytes.extend(compress(Compression::Gzip));
+        assert_eq!(decompress(&bytes), format!("{CSV}{CSV}"));
+    }
+
+    #[test]
+    fn passes_short_and_empty_plain_input_through() {
+        assert_eq!(decompress(b""), "");
+        assert_eq!(decompress(b"a"), "a");
+        assert_eq!(decompress(b"\x1f"), "\x1f");
+    }
+
+    #[test]
+    fn detects_compression_from_extension() {
+        assert_eq!(
+            Compression::from_extension(Path::new("out.csv.gz")),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_extension(Path::new("out.csv.zst")),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_extension(Path::new("out.csv")),
+            Compression::None
+        );
+        assert_eq!(
+            Compression::from_extension(Path::new("-")),
+            Compression::None
+        );
+    }
+}
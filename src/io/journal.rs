@@ -0,0 +1,389 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::event::TransactionEvent,
+    io::atomic::{parent_dir, sync_dir},
+    worker::policy::Policy,
+};
+
+/// Version written in the first line of every journal; [`read`] refuses
+/// newer ones.
+///
+/// Version 2 added the run's [`Policy`] to the header. Version 1 journals
+/// carry none and replay under the default policy.
+pub const JOURNAL_VERSION: u32 = 2;
+
+/// Entries between two fsyncs under [`FsyncPolicy::Batch`].
+pub const BATCH_SIZE: u64 = 1024;
+
+/// When journal entries are forced to disk.
+///
+/// Every entry is handed to the operating system before its event is applied,
+/// so a crash of the process alone never loses one. The policy decides what
+/// survives a power loss or kernel crash.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    /// Fsync every entry before its event is applied.
+    #[default]
+    Always,
+    /// Fsync every `BATCH_SIZE` (1024) entries and when the journal is closed.
+    Batch,
+    /// Never fsync; leave it to the operating system.
+    Never,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Header {
+    version: u32,
+    #[serde(default)]
+    policy: Policy,
+}
+
+#[derive(serde::Serialize)]
+struct EntryOut<'a> {
+    seq: u64,
+    event: &'a TransactionEvent,
+}
+
+#[derive(serde::Deserialize)]
+struct EntryIn {
+    seq: u64,
+    event: TransactionEvent,
+}
+
+/// Write-ahead journal of the events applied to a ledger.
+///
+/// The journal is a JSON Lines file: a `{"version":2,"policy":{...}}` header
+/// recording the run's [`Policy`], then one `{"seq":N,"event":{...}}` entry
+/// per event, numbered from 1. Replaying the entries, in order and under that
+/// policy, on top of the ledger the run started from reproduces the ledger
+/// the run ended with.
+///
+/// # Examples
+/// ```
+/// use transaction_parser::common::{currency::Currency, event::TransactionEvent, money::Money};
+/// use transaction_parser::io::journal::{self, FsyncPolicy, Journal};
+/// use transaction_parser::worker::policy::Policy;
+///
+/// let path = std::env::temp_dir().join(format!("journal_doc_{}.jsonl", std::process::id()));
+/// let policy = Policy {
+///     redispute: true,
+///     ..Policy::default()
+/// };
+///
+/// let mut journal = Journal::create(&path, FsyncPolicy::Always, &policy).unwrap();
+/// journal
+///     .append(&TransactionEvent::Dispute {
+///         client: 1,
+///         tx: 7,
+///         amount: None,
+///     })
+///     .unwrap();
+/// journal.close().unwrap();
+///
+/// let replay = journal::read(&path).unwrap();
+/// assert_eq!(replay.events.len(), 1);
+/// assert_eq!(replay.policy, policy);
+/// assert!(!replay.torn_tail);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    out: BufWriter<File>,
+    fsync: FsyncPolicy,
+    seq: u64,
+}
+
+impl Journal {
+    /// Creates the journal at `path`, replacing any previous one, and
+    /// durably writes its header with the `policy` the run applies.
+    pub fn create(path: &Path, fsync: FsyncPolicy, policy: &Policy) -> io::Result<Self> {
+        let mut journal = Self {
+            path: path.to_path_buf(),
+            out: BufWriter::new(File::create(path).map_err(|e| with_path(path, e))?),
+            fsync,
+            seq: 0,
+        };
+        journal.write_line(&Header {
+            version: JOURNAL_VERSION,
+            policy: *policy,
+        })?;
+        journal.sync()?;
+        sync_dir(parent_dir(path)).map_err(|e| with_path(path, e))?;
+        Ok(journal)
+    }
+
+    /// Appends `event`. Call it before applying the event, so the journal is
+    /// never behind the ledger.
+    pub fn append(&mut self, event: &TransactionEvent) -> io::Result<()> {
+        self.seq += 1;
+        self.write_line(&EntryOut {
+            seq: self.seq,
+            event,
+        })?;
+        match self.fsync {
+            FsyncPolicy::Always => self.sync(),
+            FsyncPolicy::Batch if self.seq.is_multiple_of(BATCH_SIZE) => self.sync(),
+            FsyncPolicy::Batch | FsyncPolicy::Never => Ok(()),
+        }
+    }
+
+    /// Flushes the journal and, unless the policy is `never`, fsyncs it.
+    pub fn close(mut self) -> io::Result<()> {
+        match self.fsync {
+            FsyncPolicy::Always | FsyncPolicy::Batch => self.sync(),
+            FsyncPolicy::Never => self.out.flush().map_err(|e| with_path(&self.path, e)),
+        }
+    }
+
+    fn write_line<T: serde::Serialize>(&mut self, value: &T) -> io::Result<()> {
+        let mut line = serde_json::to_vec(value)?;
+        line.push(b'\n');
+        // One write per entry, so a crash tears at most the last one.
+        self.out
+            .write_all(&line)
+            .and_then(|()| self.out.flush())
+            .map_err(|e| with_path(&self.path, e))
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.out
+            .flush()
+            .and_then(|()| self.out.get_ref().sync_data())
+            .map_err(|e| with_path(&self.path, e))
+    }
+}
+
+/// Events read back from a journal, in order.
+#[derive(Debug)]
+pub struct Replay {
+    /// The policy the journaled run applied, from the header.
+    pub policy: Policy,
+    pub events: Vec<TransactionEvent>,
+    /// An incomplete last entry, left by a crash in the middle of a write,
+    /// was dropped. Its event was never applied.
+    pub torn_tail: bool,
+}
+
+/// Reads every entry of the journal at `path`.
+///
+/// Only the last line may be incomplete; it is dropped and reported in
+/// [`Replay::torn_tail`]. Anything else that does not parse, or entries out
+/// of sequence, is an `InvalidData` error naming the line.
+pub fn read(path: &Path) -> io::Result<Replay> {
+    let mut input = BufReader::new(File::open(path).map_err(|e| with_path(path, e))?);
+    let mut replay = Replay {
+        policy: Policy::default(),
+        events: Vec::new(),
+        torn_tail: false,
+    };
+
+    let mut buf = Vec::new();
+    let mut line_no = 0u64;
+    loop {
+        buf.clear();
+        if input
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| with_path(path, e))?
+            == 0
+        {
+            break;
+        }
+        line_no += 1;
+        let complete = buf.ends_with(b"\n");
+        let invalid = |msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{line_no}: {msg}", path.display()),
+            )
+        };
+
+        if line_no == 1 {
+            match serde_json::from_slice::<Header>(&buf) {
+                Ok(header) if (1..=JOURNAL_VERSION).contains(&header.version) => {
+                    replay.policy = header.policy;
+                    continue;
+                }
+                Ok(header) => {
+                    return Err(invalid(format!(
+                        "unsupported journal version {} (expected 1 to {JOURNAL_VERSION})",
+                        header.version
+                    )));
+                }
+                Err(_) if !complete => {
+                    replay.torn_tail = true;
+                    break;
+                }
+                Err(e) => return Err(invalid(format!("invalid journal header: {e}"))),
+            }
+        }
+
+        match serde_json::from_slice::<EntryIn>(&buf) {
+            Ok(entry) => {
+                let expected = replay.events.len() as u64 + 1;
+                if entry.seq != expected {
+                    return Err(invalid(format!(
+                        "expected entry {expected}, found entry {}",
+                        entry.seq
+                    )));
+                }
+                replay.events.push(entry.event);
+            }
+            Err(_) if !complete => {
+                replay.torn_tail = true;
+                break;
+            }
+            Err(e) => return Err(invalid(format!("invalid journal entry: {e}"))),
+        }
+    }
+    Ok(replay)
+}
+
+fn with_path(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{
+        common::{
+            currency::Currency,
+            event::{EventKind, EventKinds},
+            money::Money,
+        },
+        worker::policy::{NegativeAvailablePolicy, WithdrawalDisputePolicy},
+    };
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "transaction_parser_journal_{name}_{}.jsonl",
+            std::process::id()
+        ))
+    }
+
+    fn deposit(tx: u32) -> TransactionEvent {
+        TransactionEvent::Deposit {
+            client: 1,
+            tx,
+            amount: Money::new(12_500),
+            currency: Currency::Usd,
+        }
+    }
+
+    #[test]
+    fn entries_round_trip_in_order() {
+        let path = temp_path("round_trip");
+        for fsync in [FsyncPolicy::Always, FsyncPolicy::Batch, FsyncPolicy::Never] {
+            let mut journal = Journal::create(&path, fsync, &Policy::default()).unwrap();
+            journal.append(&deposit(1)).unwrap();
+            journal
+                .append(&TransactionEvent::Chargeback {
+                    client: 1,
+                    tx: 1,
+                    amount: None,
+                })
+                .unwrap();
+            journal.close().unwrap();
+
+            let replay = read(&path).unwrap();
+            assert!(!replay.torn_tail);
+            assert_eq!(
+                format!("{:?}", replay.events),
+                format!(
+                    "{:?}",
+                    [
+                        deposit(1),
+                        TransactionEvent::Chargeback {
+                            client: 1,
+                            tx: 1,
+                            amount: None,
+                        }
+                    ]
+                )
+            );
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            [
+                r#"{"version":2,"policy":{"withdrawal_disputes":"ignore","negative_available":"allow","redispute":false,"max_disputes":null,"locked_accepts":"resolve,chargeback"}}"#,
+                r#"{"seq":1,"event":{"type":"deposit","client":1,"tx":1,"amount":"1.2500","currency":"USD"}}"#,
+                r#"{"seq":2,"event":{"type":"chargeback","client":1,"tx":1}}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn the_header_records_the_run_policy() {
+        let path = temp_path("policy");
+        let policy = Policy {
+            withdrawal_disputes: WithdrawalDisputePolicy::Hold,
+            negative_available: NegativeAvailablePolicy::HoldAvailable,
+            redispute: true,
+            max_disputes: Some(2),
+            locked_accepts: EventKinds::NONE.with(EventKind::Deposit),
+        };
+        Journal::create(&path, FsyncPolicy::Never, &policy)
+            .unwrap()
+            .close()
+            .unwrap();
+        let replay = read(&path).unwrap();
+        assert_eq!(replay.policy, policy);
+
+        // Version 1 journals have no policy in their header.
+        fs::write(&path, "{\"version\":1}\n").unwrap();
+        let replay = read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(replay.policy, Policy::default());
+    }
+
+    #[test]
+    fn an_incomplete_last_entry_is_dropped() {
+        let path = temp_path("torn");
+        let mut journal = Journal::create(&path, FsyncPolicy::Never, &Policy::default()).unwrap();
+        journal.append(&deposit(1)).unwrap();
+        journal.close().unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"seq":2,"event":{"type":"dep"#).unwrap();
+        drop(file);
+
+        let replay = read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(replay.events.len(), 1);
+        assert!(replay.torn_tail);
+    }
+
+    #[test]
+    fn corrupt_or_missing_entries_are_errors() {
+        let path = temp_path("corrupt");
+        let cases = [
+            (
+                "{\"version\":1}\n{\"seq\":1,\"event\":{\"type\":\"refund\"}}\n",
+                ":2: invalid journal entry",
+            ),
+            (
+                "{\"version\":1}\n{\"seq\":2,\"event\":{\"type\":\"dispute\",\"client\":1,\"tx\":1}}\n",
+                ":2: expected entry 1, found entry 2",
+            ),
+            ("{\"version\":3}\n", ":1: unsupported journal version 3"),
+        ];
+        for (text, error) in cases {
+            fs::write(&path, text).unwrap();
+            let err = read(&path).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains(error), "{err}");
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
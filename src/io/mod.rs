@@ -1,2 +1,8 @@
+pub mod atomic;
+pub mod compression;
+#[cfg(feature = "serde")]
+pub mod journal;
 pub mod reader;
+pub mod rejects;
+pub mod source;
 pub mod writer;
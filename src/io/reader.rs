@@ -1,23 +1,210 @@
-use crate::common::{event::TransactionEvent, money::Money};
-use std::{io::Read, str::FromStr};
+use crate::common::{
+    currency::Currency,
+    event::TransactionEvent,
+    money::{Money, RoundingPolicy},
+};
+#[cfg(feature = "serde")]
+use serde_json::value::RawValue;
+#[cfg(feature = "serde")]
+use std::io::BufRead;
+use std::io::{self, Read};
 
-#[derive(serde::Deserialize)]
 /// Internal CSV row representation matching the input headers. The amount
-/// field stays empty for dispute/resolve/chargeback rows.
+/// field is optional for dispute/resolve/chargeback rows; the `currency`
+/// column is optional.
 struct CsvRow {
-    #[serde(rename = "type")]
     tx_type: String,
     client: u16,
     tx: u32,
-    // amount is blank for dispute/resolve/chargeback
+    // amount may be blank for dispute/resolve/chargeback
     amount: Option<String>,
+    currency: Option<String>,
+}
+
+impl CsvRow {
+    /// Reads the row's fields by header name; blank optional fields are `None`.
+    fn from_record(
+        headers: &csv::StringRecord,
+        record: &csv::StringRecord,
+    ) -> Result<Self, String> {
+        let optional = |name| {
+            field(headers, record, name)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        Ok(CsvRow {
+            tx_type: field(headers, record, "type")
+                .ok_or("missing field `type`")?
+                .to_string(),
+            client: number_field(headers, record, "client")?,
+            tx: number_field(headers, record, "tx")?,
+            amount: optional("amount"),
+            currency: optional("currency"),
+        })
+    }
+}
+
+/// The field under header `name`, if the input has that column.
+fn field<'r>(
+    headers: &csv::StringRecord,
+    record: &'r csv::StringRecord,
+    name: &str,
+) -> Option<&'r str> {
+    headers
+        .iter()
+        .position(|h| h.trim() == name)
+        .and_then(|i| record.get(i))
+}
+
+fn number_field<T>(
+    headers: &csv::StringRecord,
+    record: &csv::StringRecord,
+    name: &str,
+) -> Result<T, String>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = field(headers, record, name).ok_or_else(|| format!("missing field `{name}`"))?;
+    value
+        .parse()
+        .map_err(|e| format!("invalid {name} {value:?}: {e}"))
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+/// Internal JSONL object representation, e.g.
+/// `{"type":"deposit","client":1,"tx":1,"amount":"1.5"}`. The amount may be a
+/// string or a number and may be missing or `null` for dispute/resolve/chargeback.
+/// A number is read from its original text, the same as a string.
+struct JsonRow {
+    #[serde(rename = "type")]
+    tx_type: String,
+    client: u16,
+    tx: u32,
+    #[serde(default)]
+    amount: Option<Box<RawValue>>,
+    #[serde(default)]
+    currency: Option<String>,
+}
+
+/// Just the `amount` of a JSONL object, as written.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct JsonAmount {
+    #[serde(default)]
+    amount: Option<Box<RawValue>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<JsonRow> for CsvRow {
+    type Error = String;
+
+    fn try_from(row: JsonRow) -> Result<Self, Self::Error> {
+        let amount = match row.amount {
+            None => None,
+            Some(raw) => match serde_json::from_str(raw.get()).map_err(|e| e.to_string())? {
+                serde_json::Value::Null => None,
+                // Same as an empty CSV field.
+                serde_json::Value::String(s) if s.trim().is_empty() => None,
+                serde_json::Value::String(s) => Some(s),
+                // The number's text as written, so it is parsed like a string
+                // amount and never via `f64`.
+                serde_json::Value::Number(_) => Some(raw.get().to_string()),
+                other => {
+                    return Err(format!(
+                        "amount must be a string or a number, got {other} for client {} tx {}",
+                        row.client, row.tx
+                    ));
+                }
+            },
+        };
+        Ok(CsvRow {
+            tx_type: row.tx_type,
+            client: row.client,
+            tx: row.tx,
+            amount,
+            currency: row.currency,
+        })
+    }
+}
+
+/// Original text of an input row, kept verbatim for reporting.
+///
+/// Fields are looked up by header name, so they are available even when the
+/// row fails to deserialize (for example a non-numeric `client`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RawRow {
+    pub tx_type: String,
+    pub client: String,
+    pub tx: String,
+    pub amount: String,
+}
+
+impl RawRow {
+    fn from_record(headers: &csv::StringRecord, record: &csv::StringRecord) -> Self {
+        let field = |name| field(headers, record, name).unwrap_or_default().to_string();
+        Self {
+            tx_type: field("type"),
+            client: field("client"),
+            tx: field("tx"),
+            amount: field("amount"),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn from_json(value: &serde_json::Value) -> Self {
+        let field = |name: &str| match value.get(name) {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        };
+        Self {
+            tx_type: field("type"),
+            client: field("client"),
+            tx: field("tx"),
+            amount: field("amount"),
+        }
+    }
+}
+
+/// A single input row: where it came from, what it said and what it parsed to.
+#[derive(Debug)]
+pub struct InputRow {
+    /// 1-based line number in the input; the header is line 1.
+    pub line: u64,
+    pub raw: RawRow,
+    pub event: Result<TransactionEvent, String>,
+    /// The amount was finer than its currency's minor unit (4 decimal places
+    /// without a currency) and was rounded under the [`RoundingPolicy`].
+    pub rounded: bool,
+}
+
+impl InputRow {
+    fn new(line: u64, raw: RawRow, parsed: Result<(TransactionEvent, bool), String>) -> Self {
+        let (event, rounded) = match parsed {
+            Ok((event, rounded)) => (Ok(event), rounded),
+            Err(e) => (Err(e), false),
+        };
+        Self {
+            line,
+            raw,
+            event,
+            rounded,
+        }
+    }
 }
 
 /// Reads and validates transaction rows from a CSV reader.
 ///
-/// Supported headers: `type,client,tx,amount`.
+/// Supported headers: `type,client,tx,amount`, plus an optional `currency`
+/// (ISO code such as `USD`; amounts then round to that currency's decimals).
 /// Normalizes the `type` field to lowercase and requires `amount` for
-/// `deposit` and `withdrawal` rows; errors include client/tx context.
+/// `deposit` and `withdrawal` rows; on `dispute`, `resolve` and `chargeback`
+/// rows it is optional. Errors include client/tx context.
+/// Amounts must be positive plain decimals such as `1.25` (no exponent, NaN
+/// or infinity); digits beyond the currency's minor unit (4 decimal places
+/// without a currency) are rounded half-even.
 ///
 /// # Examples
 ///
@@ -38,64 +225,305 @@ struct CsvRow {
 pub fn read_transactions<R: Read>(
     rdr: &mut csv::Reader<R>,
 ) -> impl Iterator<Item = Result<TransactionEvent, String>> + '_ {
-    // Map each CSV row into a domain `TransactionEvent`, normalizing type
-    // names and validating required amounts for deposit/withdrawal.
-    rdr.deserialize::<CsvRow>().map(|res| {
-        let row = res.map_err(|e| e.to_string())?;
-        let kind = row.tx_type.trim().to_ascii_lowercase();
-
-        match kind.as_str() {
-            "deposit" => {
-                let amt_str = row.amount.ok_or_else(|| {
-                    format!(
-                        "deposit missing amount for client {} tx {}",
-                        row.client, row.tx
-                    )
-                })?;
-                let amount = Money::from_str(&amt_str).map_err(|e| e.to_string())?;
+    read_rows(rdr, RoundingPolicy::default())
+        .map(|row| row.map_err(|e| e.to_string()).and_then(|row| row.event))
+}
 
-                Ok(TransactionEvent::Deposit {
+/// Like [`read_transactions`], but keeps the line number and the original
+/// fields of every row so callers can report on rows they skip, and applies
+/// `rounding` to amounts finer than their currency's minor unit.
+///
+/// Malformed rows come back as rows with an `Err` event. An error reading
+/// the input itself (I/O, decompression) is returned as `Err` instead and
+/// ends the iteration, since the rest of the input is unknown.
+///
+/// # Examples
+///
+/// ```
+/// use transaction_parser::common::money::RoundingPolicy;
+/// use transaction_parser::io::reader::read_rows;
+/// use csv::ReaderBuilder;
+///
+/// let data = "type,client,tx,amount\n\
+/// deposit,1,10,1.25\n\
+/// deposit,x,11,0.25\n\
+/// deposit,1,12,0.00005\n";
+/// let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+/// let rows: Vec<_> = read_rows(&mut rdr, RoundingPolicy::HalfUp)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+///
+/// assert_eq!(rows[0].line, 2);
+/// assert!(rows[0].event.is_ok());
+/// assert_eq!(rows[1].line, 3);
+/// assert_eq!(rows[1].raw.client, "x");
+/// assert!(rows[1].event.is_err());
+/// assert!(rows[2].rounded);
+/// ```
+pub fn read_rows<R: Read>(
+    rdr: &mut csv::Reader<R>,
+    rounding: RoundingPolicy,
+) -> impl Iterator<Item = io::Result<InputRow>> + '_ {
+    // A broken header surfaces again as an error on the first record.
+    let headers = rdr.headers().cloned().unwrap_or_default();
+
+    let mut failed = false;
+    rdr.records().map_while(move |res| {
+        if failed {
+            return None;
+        }
+        Some(match res {
+            Ok(record) => {
+                let line = record.position().map_or(0, |p| p.line());
+                let raw = RawRow::from_record(&headers, &record);
+                let parsed =
+                    CsvRow::from_record(&headers, &record).and_then(|row| parse_row(row, rounding));
+                Ok(InputRow::new(line, raw, parsed))
+            }
+            // The input itself could not be read: it ends here.
+            Err(e) if e.is_io_error() => {
+                failed = true;
+                match e.into_kind() {
+                    csv::ErrorKind::Io(e) => Err(e),
+                    _ => unreachable!("checked by is_io_error"),
+                }
+            }
+            Err(e) => Ok(InputRow::new(
+                e.position().map_or(0, |p| p.line()),
+                RawRow::default(),
+                Err(e.to_string()),
+            )),
+        })
+    })
+}
+
+/// Reads and validates transactions from JSON Lines, one object per line.
+///
+/// Each line looks like `{"type":"deposit","client":1,"tx":1,"amount":"1.5"}`.
+/// Validation matches [`read_transactions`]: types are case-insensitive,
+/// `amount` is required for `deposit` and `withdrawal`, and errors include
+/// client/tx context. Blank lines are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use transaction_parser::io::reader::read_jsonl_transactions;
+/// use transaction_parser::common::event::TransactionEvent;
+///
+/// let data = "{\"type\":\"deposit\",\"client\":1,\"tx\":10,\"amount\":\"1.25\"}\n\
+/// {\"type\":\"Dispute\",\"client\":1,\"tx\":10}\n";
+/// let events: Vec<_> = read_jsonl_transactions(data.as_bytes()).collect();
+///
+/// assert!(matches!(events[0], Ok(TransactionEvent::Deposit { client: 1, tx: 10, .. })));
+/// assert!(matches!(events[1], Ok(TransactionEvent::Dispute { client: 1, tx: 10, amount: None })));
+/// ```
+#[cfg(feature = "serde")]
+pub fn read_jsonl_transactions<R: BufRead>(
+    rdr: R,
+) -> impl Iterator<Item = Result<TransactionEvent, String>> {
+    read_jsonl_rows(rdr, RoundingPolicy::default())
+        .map(|row| row.map_err(|e| e.to_string()).and_then(|row| row.event))
+}
+
+/// Like [`read_jsonl_transactions`], but keeps the line number and the
+/// original fields of every row and applies `rounding`, as [`read_rows`]
+/// does for CSV. An I/O error, including invalid UTF-8, is returned as `Err`
+/// and ends the iteration.
+#[cfg(feature = "serde")]
+pub fn read_jsonl_rows<R: BufRead>(
+    rdr: R,
+    rounding: RoundingPolicy,
+) -> impl Iterator<Item = io::Result<InputRow>> {
+    let mut lines = Some(rdr.lines());
+    let mut line = 0u64;
+    std::iter::from_fn(move || {
+        loop {
+            let next = lines.as_mut()?.next()?;
+            line += 1;
+            match next {
+                Ok(text) if text.trim().is_empty() => continue,
+                Ok(text) => return Some(Ok(parse_json_line(line, &text, rounding))),
+                Err(e) => {
+                    // An I/O error (e.g. invalid UTF-8) is returned once and ends the input.
+                    lines = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(feature = "serde")]
+fn parse_json_line(line: u64, text: &str, rounding: RoundingPolicy) -> InputRow {
+    let value: serde_json::Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(e) => {
+            return InputRow::new(line, RawRow::default(), Err(format!("invalid JSON: {e}")));
+        }
+    };
+    let mut raw = RawRow::from_json(&value);
+    // `Value` keeps numbers as `f64`; report the amount's digits as written.
+    if value
+        .get("amount")
+        .is_some_and(serde_json::Value::is_number)
+        && let Ok(JsonAmount {
+            amount: Some(amount),
+        }) = serde_json::from_str(text)
+    {
+        raw.amount = amount.get().to_string();
+    }
+    let parsed = serde_json::from_str::<JsonRow>(text)
+        .map_err(|e| e.to_string())
+        .and_then(CsvRow::try_from)
+        .and_then(|row| parse_row(row, rounding));
+    InputRow::new(line, raw, parsed)
+}
+
+// Map an input row into a domain `TransactionEvent`, normalizing type
+// names and validating required amounts for deposit/withdrawal. Also
+// returns whether the amount was rounded.
+fn parse_row(row: CsvRow, rounding: RoundingPolicy) -> Result<(TransactionEvent, bool), String> {
+    let kind = row.tx_type.trim().to_ascii_lowercase();
+
+    match kind.as_str() {
+        "deposit" => {
+            let currency = parse_currency(&kind, &row)?;
+            let (amount, rounded) = parse_amount(&kind, &row, currency, rounding)?;
+            Ok((
+                TransactionEvent::Deposit {
                     client: row.client,
                     tx: row.tx,
                     amount,
-                })
-            }
-            "withdrawal" => {
-                let amt_str = row.amount.ok_or_else(|| {
-                    format!(
-                        "withdrawal missing amount for client {} tx {}",
-                        row.client, row.tx
-                    )
-                })?;
-                let amount = Money::from_str(&amt_str).map_err(|e| e.to_string())?;
-                Ok(TransactionEvent::Withdrawal {
+                    currency,
+                },
+                rounded,
+            ))
+        }
+        "withdrawal" => {
+            let currency = parse_currency(&kind, &row)?;
+            let (amount, rounded) = parse_amount(&kind, &row, currency, rounding)?;
+            Ok((
+                TransactionEvent::Withdrawal {
                     client: row.client,
                     tx: row.tx,
                     amount,
-                })
-            }
-            "dispute" => Ok(TransactionEvent::Dispute {
-                client: row.client,
-                tx: row.tx,
-            }),
-            "resolve" => Ok(TransactionEvent::Resolve {
-                client: row.client,
-                tx: row.tx,
-            }),
-            "chargeback" => Ok(TransactionEvent::Chargeback {
-                client: row.client,
-                tx: row.tx,
-            }),
-            other => Err(format!(
-                "unknown transaction type: {other} for client {} tx {}",
-                row.client, row.tx
-            )),
+                    currency,
+                },
+                rounded,
+            ))
         }
-    })
+        "dispute" => {
+            let (amount, rounded) = parse_partial_amount(&kind, &row, rounding)?;
+            Ok((
+                TransactionEvent::Dispute {
+                    client: row.client,
+                    tx: row.tx,
+                    amount,
+                },
+                rounded,
+            ))
+        }
+        "resolve" => {
+            let (amount, rounded) = parse_partial_amount(&kind, &row, rounding)?;
+            Ok((
+                TransactionEvent::Resolve {
+                    client: row.client,
+                    tx: row.tx,
+                    amount,
+                },
+                rounded,
+            ))
+        }
+        "chargeback" => {
+            let (amount, rounded) = parse_partial_amount(&kind, &row, rounding)?;
+            Ok((
+                TransactionEvent::Chargeback {
+                    client: row.client,
+                    tx: row.tx,
+                    amount,
+                },
+                rounded,
+            ))
+        }
+        other => Err(format!(
+            "unknown transaction type: {other} for client {} tx {}",
+            row.client, row.tx
+        )),
+    }
+}
+
+// Parse the optional currency of a deposit/withdrawal; blank means none.
+fn parse_currency(kind: &str, row: &CsvRow) -> Result<Currency, String> {
+    match row.currency.as_deref().map(str::trim) {
+        None | Some("") => Ok(Currency::Unspecified),
+        Some(code) => code
+            .parse()
+            .map_err(|e| format!("{kind} {e} for client {} tx {}", row.client, row.tx)),
+    }
+}
+
+// Parse the required amount of a deposit/withdrawal. Only plain decimals are
+// accepted (no exponents, NaN or infinity), and they must be positive after
+// rounding to the currency's decimal places.
+fn parse_amount(
+    kind: &str,
+    row: &CsvRow,
+    currency: Currency,
+    rounding: RoundingPolicy,
+) -> Result<(Money, bool), String> {
+    let context = || format!("for client {} tx {}", row.client, row.tx);
+
+    let text = row
+        .amount
+        .as_deref()
+        .ok_or_else(|| format!("{kind} missing amount {}", context()))?
+        .trim();
+    if !is_plain_decimal(text) {
+        return Err(format!(
+            "{kind} amount must be a plain decimal number, got {text} {}",
+            context()
+        ));
+    }
+
+    let (amount, rounded) = Money::parse_in(text, currency, rounding)
+        .map_err(|e| format!("{kind} {e} {}", context()))?;
+    if amount <= Money::zero() {
+        return Err(format!(
+            "{kind} amount must be positive, got {text} {}",
+            context()
+        ));
+    }
+    Ok((amount, rounded))
+}
+
+// Parse the optional amount of a dispute/resolve/chargeback; blank means the
+// whole open amount. Its currency is the referenced transaction's, which is
+// unknown here, so it is read at the full 4 decimal places.
+fn parse_partial_amount(
+    kind: &str,
+    row: &CsvRow,
+    rounding: RoundingPolicy,
+) -> Result<(Option<Money>, bool), String> {
+    match row.amount.as_deref().map(str::trim) {
+        None | Some("") => Ok((None, false)),
+        Some(_) => parse_amount(kind, row, Currency::Unspecified, rounding)
+            .map(|(amount, rounded)| (Some(amount), rounded)),
+    }
+}
+
+// `[+-]digits[.digits]`, with digits on at least one side of the point.
+fn is_plain_decimal(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['+', '-']).unwrap_or(s);
+    let (int, frac) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    !(int.is_empty() && frac.is_empty())
+        && int.bytes().all(|b| b.is_ascii_digit())
+        && frac.bytes().all(|b| b.is_ascii_digit())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
     // Helper: parse CSV input into collected transaction events for assertions.
     fn collect_events(input: &str) -> Vec<Result<TransactionEvent, String>> {
@@ -115,14 +543,18 @@ deposit,1,1,1.5000\nwithdrawal,1,2,0.5000\ndispute,1,1,\nresolve,1,1,\nchargebac
         let expected_withdrawal = Money::from_str("0.5000").unwrap().as_i64();
 
         match &events[0] {
-            Ok(TransactionEvent::Deposit { client, tx, amount }) => {
+            Ok(TransactionEvent::Deposit {
+                client, tx, amount, ..
+            }) => {
                 assert_eq!((*client, *tx, amount.as_i64()), (1, 1, expected_deposit));
             }
             other => panic!("unexpected deposit event: {other:?}"),
         }
 
         match &events[1] {
-            Ok(TransactionEvent::Withdrawal { client, tx, amount }) => {
+            Ok(TransactionEvent::Withdrawal {
+                client, tx, amount, ..
+            }) => {
                 assert_eq!((*client, *tx, amount.as_i64()), (1, 2, expected_withdrawal));
             }
             other => panic!("unexpected withdrawal event: {other:?}"),
@@ -130,15 +562,27 @@ deposit,1,1,1.5000\nwithdrawal,1,2,0.5000\ndispute,1,1,\nresolve,1,1,\nchargebac
 
         assert!(matches!(
             events[2],
-            Ok(TransactionEvent::Dispute { client: 1, tx: 1 })
+            Ok(TransactionEvent::Dispute {
+                client: 1,
+                tx: 1,
+                amount: None
+            })
         ));
         assert!(matches!(
             events[3],
-            Ok(TransactionEvent::Resolve { client: 1, tx: 1 })
+            Ok(TransactionEvent::Resolve {
+                client: 1,
+                tx: 1,
+                amount: None
+            })
         ));
         assert!(matches!(
             events[4],
-            Ok(TransactionEvent::Chargeback { client: 1, tx: 1 })
+            Ok(TransactionEvent::Chargeback {
+                client: 1,
+                tx: 1,
+                amount: None
+            })
         ));
     }
 
@@ -153,6 +597,167 @@ deposit,1,1,\n";
         assert_eq!(err, "deposit missing amount for client 1 tx 1");
     }
 
+    #[test]
+    fn rejects_non_positive_and_non_decimal_amounts() {
+        let data = "type,client,tx,amount\n\
+deposit,1,1,-5.0\n\
+withdrawal,1,2,-0.5\n\
+deposit,1,3,0\n\
+deposit,1,4,0.00001\n\
+deposit,1,5,1e3\n\
+withdrawal,1,6,NaN\n\
+deposit,1,7,inf\n\
+deposit,1,8,.\n\
+deposit,1,9,1.2.3\n\
+deposit,1,10,+2.5\n\
+withdrawal,1,11,.5\n";
+        let events = collect_events(data);
+
+        let errors: Vec<String> = events[..9]
+            .iter()
+            .map(|e| e.as_ref().unwrap_err().clone())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "deposit amount must be positive, got -5.0 for client 1 tx 1",
+                "withdrawal amount must be positive, got -0.5 for client 1 tx 2",
+                "deposit amount must be positive, got 0 for client 1 tx 3",
+                "deposit amount must be positive, got 0.00001 for client 1 tx 4",
+                "deposit amount must be a plain decimal number, got 1e3 for client 1 tx 5",
+                "withdrawal amount must be a plain decimal number, got NaN for client 1 tx 6",
+                "deposit amount must be a plain decimal number, got inf for client 1 tx 7",
+                "deposit amount must be a plain decimal number, got . for client 1 tx 8",
+                "deposit amount must be a plain decimal number, got 1.2.3 for client 1 tx 9",
+            ]
+        );
+        assert!(matches!(
+            events[9],
+            Ok(TransactionEvent::Deposit { amount, .. }) if amount == Money::new(25000)
+        ));
+        assert!(matches!(
+            events[10],
+            Ok(TransactionEvent::Withdrawal { amount, .. }) if amount == Money::new(5000)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rejects_non_positive_and_exponent_jsonl_numbers() {
+        let data = br#"{"type":"deposit","client":1,"tx":1,"amount":1e3}
+{"type":"deposit","client":1,"tx":2,"amount":2.5E-1}
+{"type":"withdrawal","client":1,"tx":3,"amount":-5}
+{"type":"deposit","client":1,"tx":4,"amount":0}
+"#;
+        let errors: Vec<String> = collect_jsonl(data)
+            .into_iter()
+            .map(|row| row.event.unwrap_err())
+            .collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                "deposit amount must be a plain decimal number, got 1e3 for client 1 tx 1",
+                "deposit amount must be a plain decimal number, got 2.5E-1 for client 1 tx 2",
+                "withdrawal amount must be positive, got -5 for client 1 tx 3",
+                "deposit amount must be positive, got 0 for client 1 tx 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_optional_amounts_on_disputes_resolves_and_chargebacks() {
+        let data = "type,client,tx,amount\n\
+dispute,1,1,2.5\n\
+resolve,1,1, 1 \n\
+chargeback,1,1,\n\
+dispute,1,2,0\n\
+chargeback,1,3,1e2\n";
+        let events = collect_events(data);
+
+        assert!(matches!(
+            events[0],
+            Ok(TransactionEvent::Dispute { amount: Some(a), .. }) if a == Money::new(25_000)
+        ));
+        assert!(matches!(
+            events[1],
+            Ok(TransactionEvent::Resolve { amount: Some(a), .. }) if a == Money::new(10_000)
+        ));
+        assert!(matches!(
+            events[2],
+            Ok(TransactionEvent::Chargeback { amount: None, .. })
+        ));
+        assert_eq!(
+            events[3].as_ref().unwrap_err(),
+            "dispute amount must be positive, got 0 for client 1 tx 2"
+        );
+        assert_eq!(
+            events[4].as_ref().unwrap_err(),
+            "chargeback amount must be a plain decimal number, got 1e2 for client 1 tx 3"
+        );
+    }
+
+    #[test]
+    fn parses_optional_currency_column() {
+        let data = "type,client,tx,amount,currency\n\
+deposit,1,1,1.25,usd\n\
+withdrawal,1,2,100,JPY\n\
+deposit,1,3,2.5,\n\
+deposit,1,4,1.005,USD\n\
+deposit,1,5,1,XYZ\n\
+dispute,1,1,,\n";
+        let events = collect_events(data);
+
+        assert!(matches!(
+            events[0],
+            Ok(TransactionEvent::Deposit { amount, currency: Currency::Usd, .. })
+                if amount == Money::new(12500)
+        ));
+        assert!(matches!(
+            events[1],
+            Ok(TransactionEvent::Withdrawal {
+                currency: Currency::Jpy,
+                ..
+            })
+        ));
+        assert!(matches!(
+            events[2],
+            Ok(TransactionEvent::Deposit {
+                currency: Currency::Unspecified,
+                ..
+            })
+        ));
+        // Rounded to USD's 2 decimal places, half-even.
+        assert!(matches!(
+            events[3],
+            Ok(TransactionEvent::Deposit { amount, .. }) if amount == Money::new(10000)
+        ));
+        assert_eq!(
+            events[4].as_ref().unwrap_err(),
+            "deposit unknown currency XYZ for client 1 tx 5"
+        );
+        assert!(matches!(
+            events[5],
+            Ok(TransactionEvent::Dispute {
+                client: 1,
+                tx: 1,
+                amount: None
+            })
+        ));
+
+        #[cfg(feature = "serde")]
+        {
+            let jsonl = br#"{"type":"deposit","client":1,"tx":1,"amount":"1.25","currency":"EUR"}"#;
+            assert!(matches!(
+                collect_jsonl(jsonl)[0].event,
+                Ok(TransactionEvent::Deposit {
+                    currency: Currency::Eur,
+                    ..
+                })
+            ));
+        }
+    }
+
     #[test]
     fn reports_unknown_type_error() {
         let data = "type,client,tx,amount\n\nrefund,1,99,10\n";
@@ -162,4 +767,253 @@ deposit,1,1,\n";
         let err = events.into_iter().next().unwrap().unwrap_err();
         assert_eq!(err, "unknown transaction type: refund for client 1 tx 99");
     }
+
+    #[test]
+    fn read_rows_keeps_line_numbers_and_raw_fields() {
+        let data = "type,client,tx,amount\n\
+deposit,1,1,1.0\nwithdrawal,abc,2,0.5\ndispute,1,1,\n";
+        let mut reader = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+        let rows: Vec<InputRow> = read_rows(&mut reader, RoundingPolicy::default())
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+
+        assert!(rows[0].event.is_ok());
+        assert!(rows[1].event.is_err());
+        assert_eq!(
+            rows[1].raw,
+            RawRow {
+                tx_type: "withdrawal".into(),
+                client: "abc".into(),
+                tx: "2".into(),
+                amount: "0.5".into(),
+            }
+        );
+        assert_eq!(rows[2].raw.amount, "");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rounding_policy_applies_to_csv_and_jsonl_amounts() {
+        let csv = "type,client,tx,amount\n\
+deposit,1,1,1.00005\nwithdrawal,1,2,0.5\ndispute,1,1,\n";
+        let jsonl = br#"{"type":"deposit","client":1,"tx":1,"amount":"1.00005"}
+{"type":"withdrawal","client":1,"tx":2,"amount":0.5}
+{"type":"dispute","client":1,"tx":1}
+"#;
+        for policy in [RoundingPolicy::HalfUp, RoundingPolicy::Reject] {
+            let mut reader = csv::ReaderBuilder::new().from_reader(csv.as_bytes());
+            let csv_rows: Vec<InputRow> = read_rows(&mut reader, policy)
+                .collect::<io::Result<_>>()
+                .unwrap();
+            let jsonl_rows: Vec<InputRow> = read_jsonl_rows(&jsonl[..], policy)
+                .collect::<io::Result<_>>()
+                .unwrap();
+
+            for rows in [csv_rows, jsonl_rows] {
+                match policy {
+                    RoundingPolicy::HalfUp => {
+                        assert!(matches!(
+                            rows[0].event,
+                            Ok(TransactionEvent::Deposit { amount, .. }) if amount == Money::new(10001)
+                        ));
+                        assert_eq!(
+                            rows.iter().map(|r| r.rounded).collect::<Vec<_>>(),
+                            vec![true, false, false]
+                        );
+                    }
+                    _ => {
+                        assert_eq!(
+                            rows[0].event.as_ref().unwrap_err(),
+                            "deposit amount 1.00005 has more than 4 decimal places for client 1 tx 1"
+                        );
+                        assert!(rows.iter().all(|r| !r.rounded));
+                        assert!(rows[1].event.is_ok());
+                    }
+                }
+            }
+        }
+    }
+
+    // Helper: parse JSONL input into collected rows for assertions.
+    #[cfg(feature = "serde")]
+    fn collect_jsonl(input: &[u8]) -> Vec<InputRow> {
+        read_jsonl_rows(input, RoundingPolicy::default())
+            .collect::<io::Result<_>>()
+            .unwrap()
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn jsonl_parses_all_supported_event_types() {
+        let data = br#"{"type":"deposit","client":1,"tx":1,"amount":"1.5000"}
+{"type":"WITHDRAWAL","client":1,"tx":2,"amount":0.5}
+
+{"type":"dispute","client":1,"tx":1}
+{"type":"resolve","client":1,"tx":1,"amount":null}
+{"type":"chargeback","client":1,"tx":1,"amount":""}
+"#;
+        let rows = collect_jsonl(data);
+
+        assert_eq!(
+            rows.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![1, 2, 4, 5, 6]
+        );
+        match &rows[0].event {
+            Ok(TransactionEvent::Deposit {
+                client, tx, amount, ..
+            }) => {
+                assert_eq!(
+                    (*client, *tx, amount.to_string_4dp()),
+                    (1, 1, "1.5000".into())
+                );
+            }
+            other => panic!("unexpected deposit event: {other:?}"),
+        }
+        match &rows[1].event {
+            Ok(TransactionEvent::Withdrawal { amount, .. }) => {
+                assert_eq!(amount.to_string_4dp(), "0.5000");
+            }
+            other => panic!("unexpected withdrawal event: {other:?}"),
+        }
+        assert!(matches!(
+            rows[2].event,
+            Ok(TransactionEvent::Dispute {
+                client: 1,
+                tx: 1,
+                amount: None
+            })
+        ));
+        assert!(matches!(
+            rows[3].event,
+            Ok(TransactionEvent::Resolve {
+                client: 1,
+                tx: 1,
+                amount: None
+            })
+        ));
+        assert!(matches!(
+            rows[4].event,
+            Ok(TransactionEvent::Chargeback {
+                client: 1,
+                tx: 1,
+                amount: None
+            })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn jsonl_numeric_amounts_keep_every_digit() {
+        let data = br#"{"type":"deposit","client":1,"tx":1,"amount":123456789012345.6789}
+{"type":"deposit","client":1,"tx":2,"amount":0.00005}
+"#;
+        let rows: Vec<InputRow> = read_jsonl_rows(&data[..], RoundingPolicy::Reject)
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        match &rows[0].event {
+            Ok(TransactionEvent::Deposit { amount, .. }) => {
+                assert_eq!(amount.to_string_4dp(), "123456789012345.6789");
+            }
+            other => panic!("unexpected deposit event: {other:?}"),
+        }
+        assert!(!rows[0].rounded);
+        assert_eq!(rows[0].raw.amount, "123456789012345.6789");
+        assert_eq!(rows[1].raw.amount, "0.00005");
+        assert_eq!(
+            rows[1].event.as_ref().unwrap_err(),
+            "deposit amount 0.00005 has more than 4 decimal places for client 1 tx 2"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn jsonl_reports_errors_with_context() {
+        let data = br#"{"type":"withdrawal","client":3,"tx":7}
+{"type":"refund","client":1,"tx":99,"amount":"1"}
+{"type":"deposit","client":"abc","tx":8,"amount":"1"}
+{"type":"deposit","client":1,"tx":9,"amount":[1]}
+not json
+{"type":"deposit","client":1,"tx":10,"amount":" "}
+"#;
+        let rows = collect_jsonl(data);
+
+        assert_eq!(rows.len(), 6);
+        assert_eq!(
+            rows[0].event.as_ref().unwrap_err(),
+            "withdrawal missing amount for client 3 tx 7"
+        );
+        assert_eq!(
+            rows[1].event.as_ref().unwrap_err(),
+            "unknown transaction type: refund for client 1 tx 99"
+        );
+        assert!(rows[2].event.is_err());
+        assert_eq!(rows[2].raw.client, "abc");
+        assert_eq!(
+            rows[3].event.as_ref().unwrap_err(),
+            "amount must be a string or a number, got [1] for client 1 tx 9"
+        );
+        assert!(
+            rows[4]
+                .event
+                .as_ref()
+                .unwrap_err()
+                .starts_with("invalid JSON")
+        );
+        assert_eq!(rows[4].raw, RawRow::default());
+        assert_eq!(
+            rows[5].event.as_ref().unwrap_err(),
+            "deposit missing amount for client 1 tx 10"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn jsonl_stops_after_an_io_error() {
+        let input = b"{\"type\":\"dispute\",\"client\":1,\"tx\":1}\n\xff\n{}\n";
+        let rows: Vec<_> = read_jsonl_rows(&input[..], RoundingPolicy::default()).collect();
+
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].as_ref().unwrap().event.is_ok());
+        assert_eq!(
+            rows[1].as_ref().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    // Yields `data`, then fails as a truncated compressed stream would.
+    struct Truncated<'a>(&'a [u8]);
+
+    impl Read for Truncated<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "incomplete deflate stream",
+                ));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn csv_io_errors_end_the_input_instead_of_failing_a_row() {
+        let data = "type,client,tx,amount\ndeposit,1,1,1.0\nbogus,1,2,\n";
+        let mut reader = csv::ReaderBuilder::new().from_reader(Truncated(data.as_bytes()));
+        let rows: Vec<_> = read_rows(&mut reader, RoundingPolicy::default()).collect();
+
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].as_ref().unwrap().event.is_ok());
+        // A malformed row is still a row.
+        assert!(rows[1].as_ref().unwrap().event.is_err());
+        let err = rows[2].as_ref().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(err.to_string(), "incomplete deflate stream");
+    }
 }
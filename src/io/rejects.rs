@@ -0,0 +1,168 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::io::reader::RawRow;
+
+/// Reason code used for rows that could not be parsed into an event.
+pub const PARSE_ERROR: &str = "parse_error";
+
+/// Columns of the rejects report, written before the first row.
+const HEADERS: [&str; 8] = [
+    "source", "line", "type", "client", "tx", "amount", "reason", "detail",
+];
+
+/// Streams one CSV row per skipped or invalid input row.
+///
+/// Each row repeats the original `type,client,tx,amount` fields verbatim,
+/// together with the input it came from (`-` for stdin), its line number, a
+/// machine-readable `reason` code (see `Rejection::code` and [`PARSE_ERROR`])
+/// and a human-readable `detail`.
+///
+/// # Examples
+///
+/// ```
+/// use transaction_parser::io::reader::RawRow;
+/// use transaction_parser::io::rejects::RejectWriter;
+///
+/// let raw = RawRow {
+///     tx_type: "withdrawal".into(),
+///     client: "1".into(),
+///     tx: "7".into(),
+///     amount: "5.0".into(),
+/// };
+///
+/// let mut out = Vec::new();
+/// let mut rejects = RejectWriter::new(&mut out);
+/// rejects
+///     .write("day.csv", 3, &raw, "insufficient_funds", "insufficient available funds")
+///     .unwrap();
+/// rejects.flush().unwrap();
+/// drop(rejects);
+///
+/// let s = String::from_utf8(out).unwrap();
+/// assert_eq!(
+///     s,
+///     "source,line,type,client,tx,amount,reason,detail\n\
+/// day.csv,3,withdrawal,1,7,5.0,insufficient_funds,insufficient available funds\n"
+/// );
+/// ```
+pub struct RejectWriter<W: Write> {
+    wtr: csv::Writer<W>,
+    /// File being written, named in errors; `None` for other writers.
+    path: Option<PathBuf>,
+    has_header: bool,
+}
+
+impl RejectWriter<BufWriter<File>> {
+    /// Creates (or truncates) the rejects file at `path`.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = File::create(path).map_err(|e| with_path(path, e))?;
+        let mut rejects = Self::new(BufWriter::new(file));
+        rejects.path = Some(path.to_path_buf());
+        Ok(rejects)
+    }
+}
+
+impl<W: Write> RejectWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            wtr: csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(writer),
+            path: None,
+            has_header: false,
+        }
+    }
+
+    pub fn write(
+        &mut self,
+        source: &str,
+        line: u64,
+        raw: &RawRow,
+        reason: &str,
+        detail: &str,
+    ) -> io::Result<()> {
+        let line = line.to_string();
+        let record = [
+            source,
+            &line,
+            &raw.tx_type,
+            &raw.client,
+            &raw.tx,
+            &raw.amount,
+            reason,
+            detail,
+        ];
+        if !self.has_header {
+            self.wtr
+                .write_record(HEADERS)
+                .map_err(|e| self.error(e.into()))?;
+            self.has_header = true;
+        }
+        self.wtr
+            .write_record(record)
+            .map_err(|e| self.error(e.into()))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.wtr.flush().map_err(|e| self.error(e))
+    }
+
+    fn error(&self, e: io::Error) -> io::Error {
+        match &self.path {
+            Some(path) => with_path(path, e),
+            None => e,
+        }
+    }
+}
+
+fn with_path(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_header_only_once_and_quotes_details() {
+        let raw = RawRow {
+            tx_type: "deposit".into(),
+            client: "x".into(),
+            tx: "1".into(),
+            amount: "1.0".into(),
+        };
+
+        let mut out = Vec::new();
+        {
+            let mut rejects = RejectWriter::new(&mut out);
+            rejects
+                .write(
+                    "a.csv",
+                    2,
+                    &raw,
+                    PARSE_ERROR,
+                    "invalid digit, found in string",
+                )
+                .unwrap();
+            rejects
+                .write("-", 5, &RawRow::default(), PARSE_ERROR, "bad row")
+                .unwrap();
+            rejects.flush().unwrap();
+        }
+
+        let s = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "source,line,type,client,tx,amount,reason,detail",
+                "a.csv,2,deposit,x,1,1.0,parse_error,\"invalid digit, found in string\"",
+                "-,5,,,,,parse_error,bad row",
+            ]
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+/// Input path that selects stdin instead of a file.
+pub const STDIN: &str = "-";
+
+/// Returns true when `path` selects stdin.
+pub fn is_stdin(path: &Path) -> bool {
+    path == Path::new(STDIN)
+}
+
+/// Opens an input for reading: `-` is stdin, anything else a file path.
+///
+/// Errors mention the path, so a failure in one of many inputs can be traced.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use transaction_parser::io::source::open;
+///
+/// let err = open(Path::new("does/not/exist.csv")).err().unwrap();
+/// assert!(err.to_string().starts_with("does/not/exist.csv: "));
+/// ```
+pub fn open(path: &Path) -> io::Result<Box<dyn Read>> {
+    if is_stdin(path) {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    match File::open(path) {
+        Ok(file) => Ok(Box::new(file)),
+        Err(e) => Err(io::Error::new(e.kind(), format!("{}: {e}", path.display()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dash_selects_stdin() {
+        assert!(is_stdin(Path::new("-")));
+        assert!(!is_stdin(Path::new("./-")));
+        assert!(!is_stdin(Path::new("in.csv")));
+    }
+
+    #[test]
+    fn opens_files_and_reports_missing_paths() {
+        let mut input = String::new();
+        open(Path::new("tests/fixtures/case1_input.csv"))
+            .unwrap()
+            .read_to_string(&mut input)
+            .unwrap();
+        assert!(input.starts_with("type, client, tx, amount"));
+
+        let err = open(Path::new("tests/fixtures/missing.csv")).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("tests/fixtures/missing.csv"));
+    }
+}
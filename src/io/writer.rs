@@ -1,18 +1,62 @@
-use std::{collections::HashMap, io::Write};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+};
 
-use crate::domain::account::Account;
+use crate::{
+    common::currency::Currency,
+    domain::{
+        account::{Account, Balance},
+        ledger::Totals,
+    },
+};
 
-#[derive(serde::Serialize)]
-/// Internal CSV output row representation matching the required output headers.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Internal output row representation matching the required output headers.
 ///
-/// Headers written (in this order): `client,available,held,total,locked`.
-/// Monetary fields are formatted to 4 decimal places as strings.
+/// Headers written (in this order): `client,available,held,total,locked`, or
+/// `client,currency,available,held,total,locked` when any balance has a
+/// currency, followed by `status` when [`ReportOptions::status`] is set.
+/// Monetary fields are formatted as strings, in CSV and JSON alike, with 4
+/// decimal places or as many as the currency has.
 struct OutputRow {
     client: u16,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    currency: Option<&'static str>,
     available: String,
     held: String,
     total: String,
     locked: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    status: Option<&'static str>,
+}
+
+impl OutputRow {
+    /// `(header, value)` of each column the row has, in order.
+    fn columns(&self) -> Vec<(&'static str, String)> {
+        let mut columns = vec![("client", self.client.to_string())];
+        if let Some(currency) = self.currency {
+            columns.push(("currency", currency.to_string()));
+        }
+        columns.extend([
+            ("available", self.available.clone()),
+            ("held", self.held.clone()),
+            ("total", self.total.clone()),
+            ("locked", self.locked.to_string()),
+        ]);
+        if let Some(status) = self.status {
+            columns.push(("status", status.to_string()));
+        }
+        columns
+    }
+}
+
+/// Optional columns of the balances report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReportOptions {
+    /// Add a `status` column (`active`, `frozen`, `locked` or `closed`) after
+    /// `locked`, which only reports chargeback locks.
+    pub status: bool,
 }
 
 /// Writes account states to a CSV writer.
@@ -20,8 +64,11 @@ struct OutputRow {
 /// The output includes a header row: `client,available,held,total,locked`.
 /// For deterministic output, accounts are sorted by client id ascending before writing.
 ///
-/// Monetary fields are formatted with exactly 4 decimal places using
-/// `to_string_4dp()`.
+/// Monetary fields are formatted to their currency's decimal places with
+/// `Money::to_string_in`, so balances without a currency keep 4.
+///
+/// When any account holds a balance in a specific currency, a `currency`
+/// column follows `client` and there is one row per `(client, currency)`.
 ///
 /// # Errors
 ///
@@ -31,7 +78,7 @@ struct OutputRow {
 ///
 /// ```
 /// use std::collections::HashMap;
-/// use transaction_parser::io::writer::write_accounts;
+/// use transaction_parser::io::writer::{write_accounts, ReportOptions};
 /// use transaction_parser::domain::account::Account;
 ///
 /// let mut accounts = HashMap::new();
@@ -39,7 +86,7 @@ struct OutputRow {
 /// accounts.insert(1, Account::default());
 ///
 /// let mut out = Vec::new();
-/// write_accounts(&mut out, &accounts).unwrap();
+/// write_accounts(&mut out, &accounts, ReportOptions::default()).unwrap();
 ///
 /// let s = String::from_utf8(out).unwrap();
 /// assert!(s.starts_with("client,available,held,total,locked\n"));
@@ -50,44 +97,233 @@ struct OutputRow {
 pub fn write_accounts<W: Write>(
     writer: W,
     accounts: &HashMap<u16, Account>,
+    options: ReportOptions,
 ) -> Result<(), csv::Error> {
     let mut wtr = csv::WriterBuilder::new()
-        .has_headers(true)
+        .has_headers(false)
         .from_writer(writer);
 
-    // Deterministic output: sort by client id.
-    let mut clients: Vec<u16> = accounts.keys().copied().collect();
-    clients.sort_unstable();
+    for (i, row) in output_rows(accounts, options).enumerate() {
+        let columns = row.columns();
+        if i == 0 {
+            wtr.write_record(columns.iter().map(|(header, _)| header))?;
+        }
+        wtr.write_record(columns.iter().map(|(_, value)| value))?;
+    }
 
-    for client in clients {
-        let acc = accounts.get(&client).expect("client exists");
-        let row = OutputRow {
-            client,
-            available: acc.available.to_string_4dp(),
-            held: acc.held.to_string_4dp(),
-            total: acc.total().to_string_4dp(),
-            locked: acc.locked,
-        };
-        wtr.serialize(row)?;
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes account states as a single JSON array of
+/// `{"client","available","held","total","locked"}` objects.
+///
+/// Rows are sorted by client id and amounts are strings in their currency's
+/// decimal places, exactly as in [`write_accounts`].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use transaction_parser::io::writer::{write_accounts_json, ReportOptions};
+/// use transaction_parser::domain::account::Account;
+///
+/// let mut accounts = HashMap::new();
+/// accounts.insert(1, Account::default());
+///
+/// let mut out = Vec::new();
+/// write_accounts_json(&mut out, &accounts, ReportOptions { status: true }).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "[{\"client\":1,\"available\":\"0.0000\",\"held\":\"0.0000\",\"total\":\"0.0000\",\"locked\":false,\"status\":\"active\"}]\n"
+/// );
+/// ```
+#[cfg(feature = "serde")]
+pub fn write_accounts_json<W: Write>(
+    mut writer: W,
+    accounts: &HashMap<u16, Account>,
+    options: ReportOptions,
+) -> Result<(), serde_json::Error> {
+    let rows: Vec<OutputRow> = output_rows(accounts, options).collect();
+    serde_json::to_writer(&mut writer, &rows)?;
+    writer.write_all(b"\n").map_err(serde_json::Error::io)?;
+    writer.flush().map_err(serde_json::Error::io)
+}
+
+/// Writes account states as JSON Lines, one object per account, with the
+/// same fields, ordering and formatting as [`write_accounts_json`].
+#[cfg(feature = "serde")]
+pub fn write_accounts_jsonl<W: Write>(
+    mut writer: W,
+    accounts: &HashMap<u16, Account>,
+    options: ReportOptions,
+) -> Result<(), serde_json::Error> {
+    for row in output_rows(accounts, options) {
+        serde_json::to_writer(&mut writer, &row)?;
+        writer.write_all(b"\n").map_err(serde_json::Error::io)?;
+    }
+    writer.flush().map_err(serde_json::Error::io)
+}
+
+/// Writes ledger-wide totals as CSV, one row per currency in currency order.
+///
+/// Amounts are formatted like the balances report: 4 decimal places, or the
+/// currency's decimal places. The currency is empty for amounts without one.
+/// Columns: `currency,available,held,total`; there is no header without rows.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use transaction_parser::common::{currency::Currency, money::{Money, MoneySum}};
+/// use transaction_parser::domain::ledger::Totals;
+/// use transaction_parser::io::writer::write_totals;
+///
+/// let mut totals = BTreeMap::new();
+/// totals.insert(
+///     Currency::Usd,
+///     Totals { available: MoneySum::from(Money::new(12_500)), held: MoneySum::zero() },
+/// );
+///
+/// let mut out = Vec::new();
+/// write_totals(&mut out, &totals).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "currency,available,held,total\nUSD,1.25,0.00,1.25\n"
+/// );
+/// ```
+pub fn write_totals<W: Write>(
+    writer: W,
+    totals: &BTreeMap<Currency, Totals>,
+) -> Result<(), csv::Error> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+
+    if !totals.is_empty() {
+        wtr.write_record(["currency", "available", "held", "total"])?;
+    }
+    for (currency, sum) in totals {
+        wtr.write_record([
+            currency.code(),
+            &sum.available.to_string_in(*currency),
+            &sum.held.to_string_in(*currency),
+            &sum.total().to_string_in(*currency),
+        ])?;
     }
 
     wtr.flush()?;
     Ok(())
 }
 
+/// Writes balances whose `available` amount is negative as CSV, one row per
+/// client and currency, in that order.
+///
+/// Columns: `client,currency,available,held,total`. Amounts are formatted
+/// like the balances report. A report with no rows still has its header.
+///
+/// # Examples
+///
+/// ```
+/// use transaction_parser::common::{currency::Currency, money::Money};
+/// use transaction_parser::domain::ledger::Ledger;
+/// use transaction_parser::io::writer::write_negative_available;
+///
+/// let mut ledger = Ledger::new();
+/// let balance = ledger.get_or_create_account(3).balance_mut(Currency::Usd);
+/// balance.available = Money::new(-10_000);
+/// balance.held = Money::new(25_000);
+///
+/// let mut out = Vec::new();
+/// write_negative_available(&mut out, &ledger.negative_available()).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "client,currency,available,held,total\n3,USD,-1.00,2.50,1.50\n"
+/// );
+/// ```
+pub fn write_negative_available<W: Write>(
+    writer: W,
+    balances: &BTreeMap<(u16, Currency), Balance>,
+) -> Result<(), csv::Error> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+
+    wtr.write_record(["client", "currency", "available", "held", "total"])?;
+    for ((client, currency), balance) in balances {
+        wtr.write_record([
+            &client.to_string(),
+            currency.code(),
+            &balance.available.to_string_in(*currency),
+            &balance.held.to_string_in(*currency),
+            &balance.total().to_string_in(*currency),
+        ])?;
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
+
+// Output rows in deterministic order: sorted by client id, then currency.
+fn output_rows(
+    accounts: &HashMap<u16, Account>,
+    options: ReportOptions,
+) -> impl Iterator<Item = OutputRow> + '_ {
+    let with_currency = accounts
+        .values()
+        .flat_map(|acc| acc.balances.keys())
+        .any(|currency| *currency != Currency::Unspecified);
+
+    let mut clients: Vec<u16> = accounts.keys().copied().collect();
+    clients.sort_unstable();
+
+    clients.into_iter().flat_map(move |client| {
+        let acc = accounts.get(&client).expect("client exists");
+        let mut balances: Vec<(Currency, Balance)> =
+            acc.balances.iter().map(|(c, b)| (*c, *b)).collect();
+        // An account that never held funds still gets a (zero) row.
+        if balances.is_empty() {
+            balances.push((Currency::Unspecified, Balance::default()));
+        }
+
+        balances
+            .into_iter()
+            .map(move |(currency, balance)| OutputRow {
+                client,
+                currency: with_currency.then_some(currency.code()),
+                available: balance.available.to_string_in(currency),
+                held: balance.held.to_string_in(currency),
+                total: balance.total().to_string_in(currency),
+                locked: acc.is_locked(),
+                status: options.status.then_some(acc.status.name()),
+            })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{collections::HashMap, str::FromStr};
+    use crate::{
+        common::money::{Money, MoneySum},
+        domain::account::AccountStatus,
+    };
+    use std::{
+        collections::{BTreeMap, HashMap},
+        str::FromStr,
+    };
 
     // Helper: writes accounts to a Vec<u8> and returns UTF-8 string.
     fn write_to_string(accounts: &HashMap<u16, Account>) -> String {
         let mut out = Vec::new();
-        write_accounts(&mut out, accounts).unwrap();
+        write_accounts(&mut out, accounts, ReportOptions::default()).unwrap();
         String::from_utf8(out).unwrap()
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn writes_header_and_rows_in_sorted_client_order() {
         // Create accounts inserted in non-sorted order to prove deterministic sorting.
         let mut accounts = HashMap::new();
@@ -96,10 +332,10 @@ mod tests {
         // format to "0.0000". If your `Account` doesn't implement Default, replace
         // these with the appropriate constructor for your type.
         let mut acc_2 = Account::default();
-        acc_2.locked = true;
+        acc_2.status = AccountStatus::Locked;
 
         let mut acc_1 = Account::default();
-        acc_1.locked = false;
+        acc_1.status = AccountStatus::Active;
 
         accounts.insert(2, acc_2);
         accounts.insert(1, acc_1);
@@ -130,9 +366,9 @@ mod tests {
 
         // If your Account type doesn't allow direct field access, replace with
         // your domain methods (e.g., acc.available = Money::from_str("1.2500")?...).
-        acc.available = crate::common::money::Money::from_str("1.2500").unwrap();
-        acc.held = crate::common::money::Money::from_str("0.5000").unwrap();
-        acc.locked = false;
+        acc.balance_mut(Currency::Unspecified).available = Money::from_str("1.2500").unwrap();
+        acc.balance_mut(Currency::Unspecified).held = Money::from_str("0.5000").unwrap();
+        acc.status = AccountStatus::Active;
 
         accounts.insert(7, acc);
 
@@ -143,4 +379,151 @@ mod tests {
         // total should be 1.7500 if total() = available + held.
         assert_eq!(lines[1], "7,1.2500,0.5000,1.7500,false");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_and_jsonl_match_csv_rows() {
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            9,
+            Account {
+                balances: BTreeMap::from([(
+                    Currency::Unspecified,
+                    Balance {
+                        available: Money::from_str("1.2500").unwrap(),
+                        held: Money::from_str("0.5000").unwrap(),
+                    },
+                )]),
+                status: AccountStatus::Locked,
+            },
+        );
+        accounts.insert(3, Account::default());
+
+        let mut json = Vec::new();
+        write_accounts_json(&mut json, &accounts, ReportOptions::default()).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"client": 3, "available": "0.0000", "held": "0.0000", "total": "0.0000", "locked": false},
+                {"client": 9, "available": "1.2500", "held": "0.5000", "total": "1.7500", "locked": true},
+            ])
+        );
+
+        let mut jsonl = Vec::new();
+        write_accounts_jsonl(&mut jsonl, &accounts, ReportOptions::default()).unwrap();
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"client":3,"available":"0.0000","held":"0.0000","total":"0.0000","locked":false}"#,
+                r#"{"client":9,"available":"1.2500","held":"0.5000","total":"1.7500","locked":true}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_one_row_per_client_and_currency() {
+        let mut usd_and_jpy = Account::default();
+        usd_and_jpy.balance_mut(Currency::Usd).available = Money::from_str("10.5").unwrap();
+        *usd_and_jpy.balance_mut(Currency::Jpy) = Balance {
+            available: Money::from_str("100").unwrap(),
+            held: Money::from_str("50").unwrap(),
+        };
+        let mut no_currency = Account::default();
+        no_currency.balance_mut(Currency::Unspecified).available = Money::from_str("1").unwrap();
+
+        let mut accounts = HashMap::new();
+        accounts.insert(2, no_currency);
+        accounts.insert(1, usd_and_jpy);
+        accounts.insert(3, Account::default());
+
+        let s = write_to_string(&accounts);
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "client,currency,available,held,total,locked",
+                "1,JPY,100,50,150,false",
+                "1,USD,10.50,0.00,10.50,false",
+                "2,,1.0000,0.0000,1.0000,false",
+                "3,,0.0000,0.0000,0.0000,false",
+            ]
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let mut jsonl = Vec::new();
+            write_accounts_jsonl(&mut jsonl, &accounts, ReportOptions::default()).unwrap();
+            let first = String::from_utf8(jsonl).unwrap();
+            assert_eq!(
+                first.lines().next().unwrap(),
+                r#"{"client":1,"currency":"JPY","available":"100","held":"50","total":"150","locked":false}"#
+            );
+        }
+    }
+
+    #[test]
+    fn status_column_is_optional_and_locked_stays_a_chargeback_flag() {
+        let mut accounts = HashMap::new();
+        for (client, status) in [
+            (1, AccountStatus::Active),
+            (2, AccountStatus::Frozen),
+            (3, AccountStatus::Locked),
+            (4, AccountStatus::Closed),
+        ] {
+            accounts.insert(
+                client,
+                Account {
+                    status,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut out = Vec::new();
+        write_accounts(&mut out, &accounts, ReportOptions { status: true }).unwrap();
+        let s = String::from_utf8(out).unwrap();
+        assert_eq!(
+            s.lines().collect::<Vec<_>>(),
+            [
+                "client,available,held,total,locked,status",
+                "1,0.0000,0.0000,0.0000,false,active",
+                "2,0.0000,0.0000,0.0000,false,frozen",
+                "3,0.0000,0.0000,0.0000,true,locked",
+                "4,0.0000,0.0000,0.0000,false,closed",
+            ]
+        );
+
+        let without = write_to_string(&accounts);
+        assert_eq!(without.lines().nth(3), Some("3,0.0000,0.0000,0.0000,true"));
+    }
+
+    #[test]
+    fn writes_totals_beyond_the_money_range() {
+        let max = MoneySum::from(Money::new(i64::MAX));
+        let mut totals = BTreeMap::new();
+        totals.insert(
+            Currency::Unspecified,
+            Totals {
+                available: max + max,
+                held: MoneySum::from(Money::new(5_000)),
+            },
+        );
+        totals.insert(Currency::Jpy, Totals::default());
+
+        let mut out = Vec::new();
+        write_totals(&mut out, &totals).unwrap();
+        let s = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "currency,available,held,total",
+                ",1844674407370955.1614,0.5000,1844674407370955.6614",
+                "JPY,0,0,0",
+            ]
+        );
+    }
 }
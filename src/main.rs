@@ -1,6 +1,18 @@
-use transaction_parser::app;
-fn main() {
-    if let Err(e) = app::run(std::env::args()) {
-        eprintln!("{e}");
+use std::process::ExitCode;
+
+use transaction_parser::{app, common::error::AppError};
+fn main() -> ExitCode {
+    match app::run(std::env::args()) {
+        Ok(summary) => ExitCode::from(summary.exit_code()),
+        Err(e) => {
+            match &e {
+                // clap renders help/version to stdout and usage errors to stderr.
+                AppError::Cli(err) => {
+                    let _ = err.print();
+                }
+                _ => eprintln!("{e}"),
+            }
+            ExitCode::from(e.exit_code())
+        }
     }
 }
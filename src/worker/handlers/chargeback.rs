@@ -1,66 +1,126 @@
 use crate::{
-    common::{error::AppError, money::Money},
+    common::{
+        error::AppError,
+        event::EventKind,
+        money::Money,
+        outcome::{Outcome, Rejection},
+    },
     domain::{
-        account::Account,
+        account::{AccountStatus, Balance},
         ledger::Ledger,
-        transaction::{TxStatus, TxType},
+        transaction::{TxStateMachine, TxType, partial_amount},
     },
+    worker::policy::Policy,
 };
 
-pub fn handle(ledger: &mut Ledger, client: u16, tx: u32) -> Result<(), AppError> {
-    // check if account is locked. If there are more common validations, consider moving to a common function
-    if ledger.get_or_create_account(client).is_locked() {
-        return Ok(());
+pub fn handle(
+    ledger: &mut Ledger,
+    client: u16,
+    tx: u32,
+    amount: Option<Money>,
+    policy: &Policy,
+    machine: &TxStateMachine,
+) -> Result<Outcome, AppError> {
+    // the account status decides which events the account accepts
+    if let Err(rejection) = ledger
+        .get_or_create_account(client)
+        .check(EventKind::Chargeback, policy.locked_accepts)
+    {
+        return Ok(rejection.into());
     }
 
-    let (tx_client, tx_type, tx_status, amount) = {
+    let (tx_client, tx_type, disputed, charged_back, currency, next_status) = {
         match ledger.txs.get(&tx) {
-            Some(t) => (t.client, t.tx_type, t.tx_status, t.amount),
-            None => return Ok(()),
+            Some(t) => (
+                t.client,
+                t.tx_type,
+                t.disputed,
+                t.charged_back,
+                t.currency,
+                machine.next(t, EventKind::Chargeback),
+            ),
+            None => return Ok(Rejection::TxNotFound.into()),
         }
     };
 
     // must match client
     if tx_client != client {
-        // log the reason before exit, or send back a clear error.
-        return Ok(());
+        return Ok(Rejection::ClientMismatch.into());
     }
 
-    // must be disputed to chargeback
-    if tx_status != TxStatus::Disputed {
-        // log the reason before exit, or send back a clear error.
-        return Ok(());
-    }
+    // the state machine decides whether the current status allows a chargeback
+    let next_status = match next_status {
+        Ok(status) => status,
+        Err(rejection) => return Ok(rejection.into()),
+    };
 
-    // chargeback is typically only valid for deposit disputes
-    if tx_type != TxType::Deposit {
-        // log the reason before exit, or send back a clear error.
-        return Ok(());
-    }
+    // without an amount, the chargeback settles the whole open dispute
+    let amount = match partial_amount(amount, disputed, currency) {
+        Ok(amount) => amount,
+        Err(rejection) => return Ok(rejection.into()),
+    };
+    let Some(charged_back) = charged_back.checked_add(amount) else {
+        return Ok(Rejection::Overflow.into());
+    };
+
+    let apply = match tx_type {
+        TxType::Deposit => apply_chargeback,
+        TxType::Withdrawal => apply_withdrawal_chargeback,
+    };
 
-    if apply_chargeback(ledger.get_or_create_account(client), amount) {
-        if let Some(t) = ledger.txs.get_mut(&tx) {
-            t.set_status(TxStatus::ChargedBack);
+    let account = ledger.get_or_create_account(client);
+    if let Err(rejection) = apply(account.balance_mut(currency), amount) {
+        return Ok(rejection.into());
+    }
+    // The whole account is frozen, not just the charged-back currency.
+    account.status = AccountStatus::Locked;
+    if let Some(t) = ledger.txs.get_mut(&tx) {
+        t.disputed = disputed - amount;
+        t.charged_back = charged_back;
+        // the dispute stays open until all of it is settled
+        if t.disputed == Money::zero() {
+            t.set_status(next_status);
         }
     }
 
-    Ok(())
+    Ok(Outcome::Applied)
 }
 
-fn apply_chargeback(acc: &mut Account, amount: Money) -> bool {
-    if acc.held >= amount {
-        acc.held -= amount;
-        acc.locked = true;
-        true
-    } else {
-        false
+fn apply_chargeback(balance: &mut Balance, amount: Money) -> Result<(), Rejection> {
+    if balance.held < amount {
+        return Err(Rejection::InsufficientHeldFunds);
     }
+    let held = balance
+        .held
+        .checked_sub(amount)
+        .ok_or(Rejection::Overflow)?;
+    balance.set(balance.available, held)
 }
+
+fn apply_withdrawal_chargeback(balance: &mut Balance, amount: Money) -> Result<(), Rejection> {
+    // the client wins: the pending refund becomes available again
+    if balance.held < amount {
+        return Err(Rejection::InsufficientHeldFunds);
+    }
+    let held = balance
+        .held
+        .checked_sub(amount)
+        .ok_or(Rejection::Overflow)?;
+    let available = balance
+        .available
+        .checked_add(amount)
+        .ok_or(Rejection::Overflow)?;
+    balance.set(available, held)
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use crate::domain::transaction::TransactionRecord;
+    use crate::{
+        common::{currency::Currency, event::EventKinds},
+        domain::transaction::{TransactionRecord, TxStatus},
+    };
 
     use super::*;
 
@@ -75,24 +135,81 @@ mod tests {
         let mut tx =
             TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
         tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
         ledger.txs.insert(tx_id, tx);
 
         // Setup: account has the disputed amount held
         let account = ledger.get_or_create_account(client_id);
-        account.available = Money::from_str("0.0").unwrap();
-        account.held = amount;
-        account.locked = false;
+        account.balance_mut(Currency::Unspecified).available = Money::from_str("0.0").unwrap();
+        account.balance_mut(Currency::Unspecified).held = amount;
+        account.status = AccountStatus::Active;
 
         // Act
-        let result = handle(&mut ledger, client_id, tx_id);
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
 
         // Assert
-        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        let account = ledger.get_or_create_account(client_id);
+        assert_eq!(
+            account.balance(Currency::Unspecified).held,
+            Money::from_str("0.0").unwrap()
+        );
+        assert_eq!(
+            account.balance(Currency::Unspecified).available,
+            Money::from_str("0.0").unwrap()
+        );
+        assert!(account.is_locked());
+
+        let tx = ledger.txs.get(&tx_id).unwrap();
+        assert_eq!(tx.tx_status, TxStatus::ChargedBack);
+    }
+
+    #[test]
+    fn test_handle_chargeback_withdrawal_credits_the_refund() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let tx_id = 300;
+        let amount = Money::from_str("10.0").unwrap();
 
+        // Setup: disputed withdrawal, its amount held as a pending refund
+        let mut tx = TransactionRecord::new(
+            tx_id,
+            client_id,
+            amount,
+            TxType::Withdrawal,
+            TxStatus::Normal,
+        );
+        tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
+        ledger.txs.insert(tx_id, tx);
         let account = ledger.get_or_create_account(client_id);
-        assert_eq!(account.held, Money::from_str("0.0").unwrap());
-        assert_eq!(account.available, Money::from_str("0.0").unwrap());
-        assert!(account.locked);
+        account.balance_mut(Currency::Unspecified).available = Money::from_str("5.0").unwrap();
+        account.balance_mut(Currency::Unspecified).held = amount;
+
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        // The client gets the withdrawn amount back.
+        let account = ledger.get_or_create_account(client_id);
+        let balance = account.balance(Currency::Unspecified);
+        assert_eq!(balance.available, Money::from_str("15.0").unwrap());
+        assert_eq!(balance.held, Money::zero());
+        assert!(account.is_locked());
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::ChargedBack);
@@ -101,8 +218,15 @@ mod tests {
     #[test]
     fn test_handle_chargeback_tx_not_found() {
         let mut ledger = Ledger::default();
-        let result = handle(&mut ledger, 1, 300);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            1,
+            300,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Rejected(Rejection::TxNotFound));
     }
 
     #[test]
@@ -121,10 +245,21 @@ mod tests {
             TxStatus::Normal,
         );
         tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
         ledger.txs.insert(tx_id, tx);
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::ClientMismatch)
+        );
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Disputed);
@@ -145,8 +280,18 @@ mod tests {
             TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
         ledger.txs.insert(tx_id, tx);
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::InvalidStatus(TxStatus::Normal))
+        );
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Normal);
@@ -165,20 +310,49 @@ mod tests {
         let mut tx =
             TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
         tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
         ledger.txs.insert(tx_id, tx);
 
         let account = ledger.get_or_create_account(client_id);
-        account.locked = true;
-
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        account.status = AccountStatus::Locked;
+        account.balance_mut(Currency::Unspecified).held = amount;
+
+        // A lock policy that accepts nothing keeps the dispute open.
+        let strict = Policy {
+            locked_accepts: EventKinds::NONE,
+            ..Policy::default()
+        };
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &strict,
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Rejected(Rejection::AccountLocked));
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Disputed);
 
+        // The default one lets the dispute finish.
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        let tx = ledger.txs.get(&tx_id).unwrap();
+        assert_eq!(tx.tx_status, TxStatus::ChargedBack);
+
         // still locked
         let account = ledger.get_or_create_account(client_id);
         assert!(account.is_locked());
+        assert_eq!(account.balance(Currency::Unspecified).held, Money::zero());
     }
 
     #[test]
@@ -191,24 +365,115 @@ mod tests {
         let mut tx =
             TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
         tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
         ledger.txs.insert(tx_id, tx);
 
         // account has less held than amount
         let account = ledger.get_or_create_account(client_id);
-        account.available = Money::from_str("0.0").unwrap();
-        account.held = Money::from_str("20.0").unwrap();
-        account.locked = false;
+        account.balance_mut(Currency::Unspecified).available = Money::from_str("0.0").unwrap();
+        account.balance_mut(Currency::Unspecified).held = Money::from_str("20.0").unwrap();
+        account.status = AccountStatus::Active;
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::InsufficientHeldFunds)
+        );
 
         let account = ledger.get_or_create_account(client_id);
-        assert_eq!(account.held, Money::from_str("20.0").unwrap());
-        assert_eq!(account.available, Money::from_str("0.0").unwrap());
-        assert!(!account.locked);
+        assert_eq!(
+            account.balance(Currency::Unspecified).held,
+            Money::from_str("20.0").unwrap()
+        );
+        assert_eq!(
+            account.balance(Currency::Unspecified).available,
+            Money::from_str("0.0").unwrap()
+        );
+        assert!(!account.is_locked());
 
         // tx status should remain Disputed if apply failed
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Disputed);
     }
+
+    #[test]
+    fn test_handle_chargeback_partial_amount() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let tx_id = 300;
+        let amount = Money::from_str("50.0").unwrap();
+
+        let mut tx =
+            TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
+        tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
+        ledger.txs.insert(tx_id, tx);
+        let account = ledger.get_or_create_account(client_id);
+        account.balance_mut(Currency::Unspecified).held = amount;
+
+        let part = Money::from_str("20.0").unwrap();
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            Some(part),
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        // Only the charged-back part leaves; the rest stays held and disputed.
+        let account = ledger.get_or_create_account(client_id);
+        assert!(account.is_locked());
+        let balance = account.balance(Currency::Unspecified);
+        assert_eq!(balance.available, Money::zero());
+        assert_eq!(balance.held, amount - part);
+        let tx = ledger.txs.get(&tx_id).unwrap();
+        assert_eq!(tx.tx_status, TxStatus::Disputed);
+        assert_eq!((tx.disputed, tx.charged_back), (amount - part, part));
+        assert_eq!(tx.disputable(), Money::zero());
+    }
+
+    #[test]
+    fn test_handle_chargeback_rejects_fractions_of_a_cent() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let tx_id = 300;
+        let amount = Money::from_str("10.00").unwrap();
+
+        let mut tx =
+            TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
+        tx.currency = Currency::Usd;
+        tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
+        ledger.txs.insert(tx_id, tx);
+        let account = ledger.get_or_create_account(client_id);
+        account.balance_mut(Currency::Usd).held = amount;
+
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            Some(Money::from_str("1.005").unwrap()),
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::AmountPrecision)
+        );
+
+        let account = ledger.get_or_create_account(client_id);
+        assert!(!account.is_locked());
+        assert_eq!(account.balance(Currency::Usd).held, amount);
+        let tx = ledger.txs.get(&tx_id).unwrap();
+        assert_eq!((tx.disputed, tx.charged_back), (amount, Money::zero()));
+    }
 }
@@ -1,20 +1,40 @@
 use crate::{
-    common::{error::AppError, money::Money},
-    domain::{account::Account, ledger::Ledger},
+    common::{
+        currency::Currency,
+        error::AppError,
+        event::EventKind,
+        money::Money,
+        outcome::{Outcome, Rejection},
+    },
+    domain::{account::Balance, ledger::Ledger},
+    worker::policy::Policy,
 };
 
-pub fn handle(ledger: &mut Ledger, client: u16, tx: u32, amount: Money) -> Result<(), AppError> {
-    // check if account is locked. If there are more common validations, consider moving to a common function
-    if ledger.get_or_create_account(client).is_locked() {
-        return Ok(());
+pub fn handle(
+    ledger: &mut Ledger,
+    client: u16,
+    tx: u32,
+    amount: Money,
+    currency: Currency,
+    policy: &Policy,
+) -> Result<Outcome, AppError> {
+    // the account status decides which events the account accepts
+    if let Err(rejection) = ledger
+        .get_or_create_account(client)
+        .check(EventKind::Deposit, policy.locked_accepts)
+    {
+        return Ok(rejection.into());
     }
 
     //check if transaction already exists
     if ledger.txs.contains_key(&tx) {
-        return Ok(());
+        return Ok(Rejection::DuplicateTx.into());
     }
 
-    apply_deposit(ledger.get_or_create_account(client), amount);
+    let balance = ledger.get_or_create_account(client).balance_mut(currency);
+    if let Err(rejection) = apply_deposit(balance, amount) {
+        return Ok(rejection.into());
+    }
 
     ledger.txs.insert(
         tx,
@@ -22,15 +42,24 @@ pub fn handle(ledger: &mut Ledger, client: u16, tx: u32, amount: Money) -> Resul
             tx_id: tx,
             client,
             amount,
+            currency,
             tx_type: crate::domain::transaction::TxType::Deposit,
             tx_status: crate::domain::transaction::TxStatus::Normal,
+            disputes: 0,
+            disputed: Money::zero(),
+            resolved: Money::zero(),
+            charged_back: Money::zero(),
         },
     );
-    Ok(())
+    Ok(Outcome::Applied)
 }
 
-fn apply_deposit(acc: &mut Account, amount: Money) {
-    acc.available += amount;
+fn apply_deposit(balance: &mut Balance, amount: Money) -> Result<(), Rejection> {
+    let available = balance
+        .available
+        .checked_add(amount)
+        .ok_or(Rejection::Overflow)?;
+    balance.set(available, balance.held)
 }
 
 #[cfg(test)]
@@ -38,24 +67,41 @@ mod tests {
     use std::str::FromStr;
 
     use super::handle;
-    use crate::{common::money::Money, domain::ledger::Ledger};
+    use crate::{
+        common::{
+            currency::Currency,
+            money::Money,
+            outcome::{Outcome, Rejection},
+        },
+        domain::{account::AccountStatus, ledger::Ledger},
+        worker::policy::Policy,
+    };
 
     #[test]
     fn deposit_applies_credit_and_records_tx() {
         let mut ledger = Ledger::new();
 
-        let _ = handle(&mut ledger, 1, 10, Money::from_str("1.2500").unwrap());
+        let outcome = handle(
+            &mut ledger,
+            1,
+            10,
+            Money::from_str("1.2500").unwrap(),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Applied);
 
         let acc = ledger.accounts().get(&1).expect("account exists");
         assert_eq!(
-            acc.available.as_i64(),
+            acc.balance(Currency::Unspecified).available.as_i64(),
             Money::from_str("1.2500").unwrap().as_i64()
         );
         assert_eq!(
-            acc.held.as_i64(),
+            acc.balance(Currency::Unspecified).held.as_i64(),
             Money::from_str("0.0000").unwrap().as_i64()
         );
-        assert!(!acc.locked);
+        assert!(!acc.is_locked());
 
         let rec = ledger.txs.get(&10).expect("tx recorded");
         assert_eq!(rec.client, 1);
@@ -72,12 +118,28 @@ mod tests {
     fn deposit_ignores_duplicate_tx_id() {
         let mut ledger = Ledger::new();
 
-        let _ = handle(&mut ledger, 1, 10, Money::from_str("1.0000").unwrap());
-        let _ = handle(&mut ledger, 1, 10, Money::from_str("9.0000").unwrap()); // duplicate tx id must be ignored
+        let _ = handle(
+            &mut ledger,
+            1,
+            10,
+            Money::from_str("1.0000").unwrap(),
+            Currency::Unspecified,
+            &Policy::default(),
+        );
+        let outcome = handle(
+            &mut ledger,
+            1,
+            10,
+            Money::from_str("9.0000").unwrap(),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap(); // duplicate tx id must be ignored
+        assert_eq!(outcome, Outcome::Rejected(Rejection::DuplicateTx));
 
         let acc = ledger.accounts().get(&1).expect("account exists");
         assert_eq!(
-            acc.available.as_i64(),
+            acc.balance(Currency::Unspecified).available.as_i64(),
             Money::from_str("1.0000").unwrap().as_i64()
         ); // unchanged
 
@@ -88,6 +150,60 @@ mod tests {
         ); // original remains
     }
 
+    #[test]
+    fn deposit_that_would_overflow_is_rejected() {
+        let mut ledger = Ledger::new();
+
+        handle(
+            &mut ledger,
+            1,
+            1,
+            Money::new(i64::MAX - 1),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        let outcome = handle(
+            &mut ledger,
+            1,
+            2,
+            Money::new(2),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Rejected(Rejection::Overflow));
+
+        let acc = ledger.accounts().get(&1).expect("account exists");
+        assert_eq!(
+            acc.balance(Currency::Unspecified).available,
+            Money::new(i64::MAX - 1)
+        ); // unchanged
+        assert!(!ledger.txs.contains_key(&2));
+
+        // The total must stay representable too, even if `available` alone fits.
+        ledger
+            .get_or_create_account(2)
+            .balance_mut(Currency::Unspecified)
+            .held = Money::new(i64::MAX);
+        let outcome = handle(
+            &mut ledger,
+            2,
+            3,
+            Money::new(1),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Rejected(Rejection::Overflow));
+        assert_eq!(
+            ledger.accounts()[&2]
+                .balance(Currency::Unspecified)
+                .available,
+            Money::zero()
+        );
+    }
+
     #[test]
     fn deposit_is_ignored_if_account_is_locked() {
         let mut ledger = Ledger::new();
@@ -95,21 +211,30 @@ mod tests {
         // Create account and lock it
         {
             let acc = ledger.get_or_create_account(1);
-            acc.locked = true;
+            acc.status = AccountStatus::Locked;
         }
 
-        let _ = handle(&mut ledger, 1, 10, Money::from_str("3.0000").unwrap());
+        let outcome = handle(
+            &mut ledger,
+            1,
+            10,
+            Money::from_str("3.0000").unwrap(),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Rejected(Rejection::AccountLocked));
 
         let acc = ledger.accounts().get(&1).expect("account exists");
         assert_eq!(
-            acc.available.as_i64(),
+            acc.balance(Currency::Unspecified).available.as_i64(),
             Money::from_str("0.0000").unwrap().as_i64()
         );
         assert_eq!(
-            acc.held.as_i64(),
+            acc.balance(Currency::Unspecified).held.as_i64(),
             Money::from_str("0.0000").unwrap().as_i64()
         );
-        assert!(acc.locked);
+        assert!(acc.is_locked());
 
         // Important: should NOT record tx when ignored due to lock
         assert!(!ledger.txs.contains_key(&10));
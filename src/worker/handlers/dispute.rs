@@ -1,51 +1,130 @@
 use crate::{
-    common::{error::AppError, money::Money},
+    common::{
+        error::AppError,
+        event::EventKind,
+        money::Money,
+        outcome::{Outcome, Rejection},
+    },
     domain::{
-        account::Account,
+        account::Balance,
         ledger::Ledger,
-        transaction::{TxStatus, TxType},
+        transaction::{TxStateMachine, TxType, partial_amount},
     },
+    worker::policy::{NegativeAvailablePolicy, Policy, WithdrawalDisputePolicy},
 };
 
-pub fn handle(ledger: &mut Ledger, client: u16, tx: u32) -> Result<(), AppError> {
-    // check if account is locked. If there are more common validations, consider moving to a common function
-    if ledger.get_or_create_account(client).is_locked() {
-        return Ok(());
+pub fn handle(
+    ledger: &mut Ledger,
+    client: u16,
+    tx: u32,
+    amount: Option<Money>,
+    policy: &Policy,
+    machine: &TxStateMachine,
+) -> Result<Outcome, AppError> {
+    // the account status decides which events the account accepts
+    if let Err(rejection) = ledger
+        .get_or_create_account(client)
+        .check(EventKind::Dispute, policy.locked_accepts)
+    {
+        return Ok(rejection.into());
     }
 
-    let (tx_client, tx_type, tx_status, amount) = {
+    let (tx_client, tx_type, disputable, currency, next_status) = {
         match ledger.txs.get(&tx) {
-            Some(t) => (t.client, t.tx_type, t.tx_status, t.amount),
-            None => return Ok(()),
+            Some(t) => (
+                t.client,
+                t.tx_type,
+                t.disputable(),
+                t.currency,
+                machine.next(t, EventKind::Dispute),
+            ),
+            None => return Ok(Rejection::TxNotFound.into()),
         }
     };
 
     // must match client
     if tx_client != client {
-        return Ok(());
+        return Ok(Rejection::ClientMismatch.into());
     }
 
-    // disputes only apply to deposits
-    if tx_type != TxType::Deposit {
-        return Ok(());
-    }
+    // withdrawals are disputable only if the policy says how
+    let apply = match (tx_type, policy.withdrawal_disputes) {
+        (TxType::Deposit, _) => apply_dispute,
+        (TxType::Withdrawal, WithdrawalDisputePolicy::Hold) => apply_withdrawal_dispute,
+        (TxType::Withdrawal, WithdrawalDisputePolicy::Ignore) => {
+            return Ok(Rejection::NotDisputable.into());
+        }
+    };
 
-    // current status must be Normal for applying dispute
-    if tx_status != TxStatus::Normal {
-        return Ok(());
-    }
+    // the state machine decides whether the current status can be disputed
+    let next_status = match next_status {
+        Ok(status) => status,
+        Err(rejection) => return Ok(rejection.into()),
+    };
+
+    // without an amount, the dispute covers everything not charged back yet
+    let amount = match partial_amount(amount, disputable, currency) {
+        Ok(amount) => amount,
+        Err(rejection) => return Ok(rejection.into()),
+    };
 
-    apply_dispute(ledger.get_or_create_account(client), amount);
+    let balance = ledger.get_or_create_account(client).balance_mut(currency);
+    // a deposit dispute takes from available, which the policy may protect
+    let amount = match tx_type {
+        TxType::Deposit => {
+            match limit_to_available(policy.negative_available, balance.available, amount) {
+                Ok(amount) => amount,
+                Err(rejection) => return Ok(rejection.into()),
+            }
+        }
+        TxType::Withdrawal => amount,
+    };
+    if let Err(rejection) = apply(balance, amount) {
+        return Ok(rejection.into());
+    }
     if let Some(t) = ledger.txs.get_mut(&tx) {
-        t.set_status(TxStatus::Disputed);
+        t.set_status(next_status);
+        t.disputes += 1;
+        t.disputed += amount;
+    }
+
+    Ok(Outcome::Applied)
+}
+
+fn limit_to_available(
+    policy: NegativeAvailablePolicy,
+    available: Money,
+    amount: Money,
+) -> Result<Money, Rejection> {
+    match policy {
+        NegativeAvailablePolicy::Allow => Ok(amount),
+        _ if available >= amount => Ok(amount),
+        NegativeAvailablePolicy::Reject => Err(Rejection::InsufficientFunds),
+        NegativeAvailablePolicy::HoldAvailable if available > Money::zero() => Ok(available),
+        NegativeAvailablePolicy::HoldAvailable => Err(Rejection::InsufficientFunds),
     }
+}
 
-    Ok(())
+fn apply_dispute(balance: &mut Balance, amount: Money) -> Result<(), Rejection> {
+    // available -> held; available may go negative under the `allow` policy
+    let available = balance
+        .available
+        .checked_sub(amount)
+        .ok_or(Rejection::Overflow)?;
+    let held = balance
+        .held
+        .checked_add(amount)
+        .ok_or(Rejection::Overflow)?;
+    balance.set(available, held)
 }
 
-fn apply_dispute(acc: &mut Account, amount: Money) {
-    acc.available -= amount;
-    acc.held += amount;
+fn apply_withdrawal_dispute(balance: &mut Balance, amount: Money) -> Result<(), Rejection> {
+    // the withdrawn funds are gone; hold their amount as a pending refund
+    let held = balance
+        .held
+        .checked_add(amount)
+        .ok_or(Rejection::Overflow)?;
+    balance.set(balance.available, held)
 }
 
 #[cfg(test)]
@@ -53,7 +132,13 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
-    use crate::domain::transaction::TransactionRecord;
+    use crate::{
+        common::currency::Currency,
+        domain::{
+            account::AccountStatus,
+            transaction::{TransactionRecord, TxStatus},
+        },
+    };
 
     #[test]
     fn test_handle_dispute_success() {
@@ -69,17 +154,27 @@ mod tests {
 
         // Setup: Ensure account has funds (deposit usually adds funds, simulating that state)
         let account = ledger.get_or_create_account(client_id);
-        account.available = amount;
+        account.balance_mut(Currency::Unspecified).available = amount;
 
         // Act
-        let result = handle(&mut ledger, client_id, tx_id);
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
 
         // Assert
-        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), Outcome::Applied);
 
         let account = ledger.get_or_create_account(client_id);
-        assert_eq!(account.available, Money::from_str("0.0").unwrap()); // Funds moved from available
-        assert_eq!(account.held, amount); // To held
+        assert_eq!(
+            account.balance(Currency::Unspecified).available,
+            Money::from_str("0.0").unwrap()
+        ); // Funds moved from available
+        assert_eq!(account.balance(Currency::Unspecified).held, amount); // To held
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Disputed);
@@ -92,10 +187,17 @@ mod tests {
         let tx_id = 100;
 
         let account = ledger.get_or_create_account(client_id);
-        account.locked = true;
+        account.status = AccountStatus::Locked;
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok()); // Should return Ok(()) early
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Rejected(Rejection::AccountLocked));
     }
 
     #[test]
@@ -104,8 +206,15 @@ mod tests {
         let client_id = 1;
         let tx_id = 100;
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Rejected(Rejection::TxNotFound));
     }
 
     #[test]
@@ -125,8 +234,18 @@ mod tests {
         );
         ledger.txs.insert(tx_id, tx);
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::ClientMismatch)
+        );
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Normal); // Status unchanged
@@ -148,13 +267,64 @@ mod tests {
         );
         ledger.txs.insert(tx_id, tx);
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Rejected(Rejection::NotDisputable));
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Normal);
     }
 
+    #[test]
+    fn test_handle_dispute_withdrawal_holds_a_pending_refund() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let tx_id = 100;
+        let amount = Money::from_str("10.0").unwrap();
+
+        let tx = TransactionRecord::new(
+            tx_id,
+            client_id,
+            amount,
+            TxType::Withdrawal,
+            TxStatus::Normal,
+        );
+        ledger.txs.insert(tx_id, tx);
+        let account = ledger.get_or_create_account(client_id);
+        account.balance_mut(Currency::Unspecified).available = Money::from_str("5.0").unwrap();
+
+        let policy = Policy {
+            withdrawal_disputes: WithdrawalDisputePolicy::Hold,
+            ..Policy::default()
+        };
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &policy,
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        // The withdrawn funds are gone: available is unchanged, held grows.
+        let balance = ledger
+            .get_or_create_account(client_id)
+            .balance(Currency::Unspecified);
+        assert_eq!(balance.available, Money::from_str("5.0").unwrap());
+        assert_eq!(balance.held, amount);
+        assert_eq!(balance.total(), Money::from_str("15.0").unwrap());
+
+        let tx = ledger.txs.get(&tx_id).unwrap();
+        assert_eq!(tx.tx_status, TxStatus::Disputed);
+    }
+
     #[test]
     fn test_handle_dispute_already_disputed() {
         let mut ledger = Ledger::default();
@@ -165,15 +335,29 @@ mod tests {
         let mut tx =
             TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
         tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
         ledger.txs.insert(tx_id, tx);
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::InvalidStatus(TxStatus::Disputed))
+        );
 
         // Account balances should not change again if logic prevents re-disputing
         // (The current implementation checks for TxStatus::Normal, so it returns early)
         let account = ledger.get_or_create_account(client_id);
-        assert_eq!(account.held, Money::from_str("0.0").unwrap());
+        assert_eq!(
+            account.balance(Currency::Unspecified).held,
+            Money::from_str("0.0").unwrap()
+        );
     }
 
     #[test]
@@ -188,16 +372,190 @@ mod tests {
         ledger.txs.insert(tx_id, tx);
 
         // Account has 0 available
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
 
         let account = ledger.get_or_create_account(client_id);
 
         // Available can go negative; held equals the disputed amount; tx becomes Disputed
-        assert_eq!(account.available, Money::from_str("-100.0").unwrap());
-        assert_eq!(account.held, amount);
+        assert_eq!(
+            account.balance(Currency::Unspecified).available,
+            Money::from_str("-100.0").unwrap()
+        );
+        assert_eq!(account.balance(Currency::Unspecified).held, amount);
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Disputed);
     }
+
+    #[test]
+    fn test_handle_dispute_partial_amount() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let amount = Money::from_str("50.0").unwrap();
+
+        for tx_id in [100, 101] {
+            let tx =
+                TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
+            ledger.txs.insert(tx_id, tx);
+        }
+        let account = ledger.get_or_create_account(client_id);
+        account.balance_mut(Currency::Unspecified).available = Money::from_str("100.0").unwrap();
+
+        // Only the disputed part moves to held.
+        let part = Money::from_str("20.0").unwrap();
+        let result = handle(
+            &mut ledger,
+            client_id,
+            100,
+            Some(part),
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        let balance = ledger
+            .get_or_create_account(client_id)
+            .balance(Currency::Unspecified);
+        assert_eq!(balance.available, Money::from_str("80.0").unwrap());
+        assert_eq!(balance.held, part);
+
+        let tx = ledger.txs.get(&100).unwrap();
+        assert_eq!(tx.tx_status, TxStatus::Disputed);
+        assert_eq!(tx.disputed, part);
+        assert_eq!(tx.disputable(), Money::from_str("30.0").unwrap());
+
+        // A dispute cannot cover more than the transaction.
+        let result = handle(
+            &mut ledger,
+            client_id,
+            101,
+            Some(Money::from_str("50.0001").unwrap()),
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::AmountExceeded)
+        );
+        let tx = ledger.txs.get(&101).unwrap();
+        assert_eq!(
+            (tx.tx_status, tx.disputed),
+            (TxStatus::Normal, Money::zero())
+        );
+    }
+
+    #[test]
+    fn test_handle_dispute_amount_in_whole_minor_units() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let tx_id = 100;
+        let amount = Money::from_str("100").unwrap();
+
+        let mut tx =
+            TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
+        tx.currency = Currency::Jpy;
+        ledger.txs.insert(tx_id, tx);
+        let account = ledger.get_or_create_account(client_id);
+        account.balance_mut(Currency::Jpy).available = amount;
+
+        // JPY has no minor unit, so half a yen cannot be disputed.
+        let dispute = |ledger: &mut Ledger, part: &str| {
+            handle(
+                ledger,
+                client_id,
+                tx_id,
+                Some(Money::from_str(part).unwrap()),
+                &Policy::default(),
+                &TxStateMachine::default(),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            dispute(&mut ledger, "40.5"),
+            Outcome::Rejected(Rejection::AmountPrecision)
+        );
+        assert_eq!(ledger.txs.get(&tx_id).unwrap().disputed, Money::zero());
+
+        assert_eq!(dispute(&mut ledger, "40"), Outcome::Applied);
+        let balance = ledger
+            .get_or_create_account(client_id)
+            .balance(Currency::Jpy);
+        assert_eq!(balance.held, Money::from_str("40").unwrap());
+    }
+
+    #[test]
+    fn test_handle_dispute_negative_available_policies() {
+        let client_id = 1;
+        let tx_id = 100;
+        let amount = Money::from_str("100.0").unwrap();
+        let dispute = |available: &str, negative_available| {
+            let mut ledger = Ledger::default();
+            let tx =
+                TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
+            ledger.txs.insert(tx_id, tx);
+            let account = ledger.get_or_create_account(client_id);
+            account.balance_mut(Currency::Unspecified).available =
+                Money::from_str(available).unwrap();
+
+            let policy = Policy {
+                negative_available,
+                ..Policy::default()
+            };
+            let result = handle(
+                &mut ledger,
+                client_id,
+                tx_id,
+                None,
+                &policy,
+                &TxStateMachine::default(),
+            );
+            let balance = ledger
+                .get_or_create_account(client_id)
+                .balance(Currency::Unspecified);
+            let tx = ledger.txs.get(&tx_id).unwrap();
+            (
+                result.unwrap(),
+                balance.available.to_string_4dp(),
+                tx.disputed.to_string_4dp(),
+            )
+        };
+
+        assert_eq!(
+            dispute("40.0", NegativeAvailablePolicy::Allow),
+            (Outcome::Applied, "-60.0000".into(), "100.0000".into())
+        );
+        assert_eq!(
+            dispute("40.0", NegativeAvailablePolicy::Reject),
+            (
+                Outcome::Rejected(Rejection::InsufficientFunds),
+                "40.0000".into(),
+                "0.0000".into()
+            )
+        );
+        assert_eq!(
+            dispute("100.0", NegativeAvailablePolicy::Reject),
+            (Outcome::Applied, "0.0000".into(), "100.0000".into())
+        );
+        // Only the available part is held, and only that part is disputed.
+        assert_eq!(
+            dispute("40.0", NegativeAvailablePolicy::HoldAvailable),
+            (Outcome::Applied, "0.0000".into(), "40.0000".into())
+        );
+        assert_eq!(
+            dispute("0.0", NegativeAvailablePolicy::HoldAvailable),
+            (
+                Outcome::Rejected(Rejection::InsufficientFunds),
+                "0.0000".into(),
+                "0.0000".into()
+            )
+        );
+    }
 }
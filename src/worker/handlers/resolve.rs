@@ -1,58 +1,115 @@
 use crate::{
-    common::{error::AppError, money::Money},
+    common::{
+        error::AppError,
+        event::EventKind,
+        money::Money,
+        outcome::{Outcome, Rejection},
+    },
     domain::{
-        account::Account,
+        account::Balance,
         ledger::Ledger,
-        transaction::{TxStatus, TxType},
+        transaction::{TxStateMachine, TxType, partial_amount},
     },
+    worker::policy::Policy,
 };
 
-pub fn handle(ledger: &mut Ledger, client: u16, tx: u32) -> Result<(), AppError> {
-    // check if account is locked. If there are more common validations, consider moving to a common function
-    if ledger.get_or_create_account(client).is_locked() {
-        return Ok(());
+pub fn handle(
+    ledger: &mut Ledger,
+    client: u16,
+    tx: u32,
+    amount: Option<Money>,
+    policy: &Policy,
+    machine: &TxStateMachine,
+) -> Result<Outcome, AppError> {
+    // the account status decides which events the account accepts
+    if let Err(rejection) = ledger
+        .get_or_create_account(client)
+        .check(EventKind::Resolve, policy.locked_accepts)
+    {
+        return Ok(rejection.into());
     }
 
-    let (tx_client, tx_type, tx_status, amount) = {
+    let (tx_client, tx_type, disputed, resolved, currency, next_status) = {
         match ledger.txs.get(&tx) {
-            Some(t) => (t.client, t.tx_type, t.tx_status, t.amount),
-            None => return Ok(()),
+            Some(t) => (
+                t.client,
+                t.tx_type,
+                t.disputed,
+                t.resolved,
+                t.currency,
+                machine.next(t, EventKind::Resolve),
+            ),
+            None => return Ok(Rejection::TxNotFound.into()),
         }
     };
 
     // must match client
     if tx_client != client {
-        return Ok(());
+        return Ok(Rejection::ClientMismatch.into());
     }
 
-    // must be disputed to resolve
-    if tx_status != TxStatus::Disputed {
-        return Ok(());
-    }
+    // the state machine decides whether the current status allows a resolve
+    let next_status = match next_status {
+        Ok(status) => status,
+        Err(rejection) => return Ok(rejection.into()),
+    };
 
-    // resolve should only apply to deposit disputes
-    if tx_type != TxType::Deposit {
-        return Ok(());
-    }
+    // without an amount, the resolve settles the whole open dispute
+    let amount = match partial_amount(amount, disputed, currency) {
+        Ok(amount) => amount,
+        Err(rejection) => return Ok(rejection.into()),
+    };
+    let Some(resolved) = resolved.checked_add(amount) else {
+        return Ok(Rejection::Overflow.into());
+    };
 
-    if apply_resolve(ledger.get_or_create_account(client), amount) {
-        if let Some(t) = ledger.txs.get_mut(&tx) {
-            t.set_status(TxStatus::Resolved);
+    let apply = match tx_type {
+        TxType::Deposit => apply_resolve,
+        TxType::Withdrawal => apply_withdrawal_resolve,
+    };
+
+    let balance = ledger.get_or_create_account(client).balance_mut(currency);
+    if let Err(rejection) = apply(balance, amount) {
+        return Ok(rejection.into());
+    }
+    if let Some(t) = ledger.txs.get_mut(&tx) {
+        t.disputed = disputed - amount;
+        t.resolved = resolved;
+        // the dispute stays open until all of it is settled
+        if t.disputed == Money::zero() {
+            t.set_status(next_status);
         }
     }
 
-    Ok(())
+    Ok(Outcome::Applied)
 }
 
-fn apply_resolve(acc: &mut Account, amount: Money) -> bool {
+fn apply_resolve(balance: &mut Balance, amount: Money) -> Result<(), Rejection> {
     // Resolve: held -> available
-    if acc.held >= amount {
-        acc.held -= amount;
-        acc.available += amount;
-        true
-    } else {
-        false
+    if balance.held < amount {
+        return Err(Rejection::InsufficientHeldFunds);
+    }
+    let held = balance
+        .held
+        .checked_sub(amount)
+        .ok_or(Rejection::Overflow)?;
+    let available = balance
+        .available
+        .checked_add(amount)
+        .ok_or(Rejection::Overflow)?;
+    balance.set(available, held)
+}
+
+fn apply_withdrawal_resolve(balance: &mut Balance, amount: Money) -> Result<(), Rejection> {
+    // the withdrawal stands: drop the pending refund
+    if balance.held < amount {
+        return Err(Rejection::InsufficientHeldFunds);
     }
+    let held = balance
+        .held
+        .checked_sub(amount)
+        .ok_or(Rejection::Overflow)?;
+    balance.set(balance.available, held)
 }
 
 #[cfg(test)]
@@ -60,7 +117,13 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
-    use crate::domain::transaction::TransactionRecord;
+    use crate::{
+        common::{currency::Currency, event::EventKinds},
+        domain::{
+            account::AccountStatus,
+            transaction::{TransactionRecord, TxStatus},
+        },
+    };
 
     #[test]
     fn test_handle_resolve_success() {
@@ -73,22 +136,76 @@ mod tests {
         let mut tx =
             TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
         tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
         ledger.txs.insert(tx_id, tx);
 
         // Setup: account has the disputed amount held
         let account = ledger.get_or_create_account(client_id);
-        account.available = Money::from_str("0.0").unwrap();
-        account.held = amount;
+        account.balance_mut(Currency::Unspecified).available = Money::from_str("0.0").unwrap();
+        account.balance_mut(Currency::Unspecified).held = amount;
 
         // Act
-        let result = handle(&mut ledger, client_id, tx_id);
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
 
         // Assert
-        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        let account = ledger.get_or_create_account(client_id);
+        assert_eq!(
+            account.balance(Currency::Unspecified).held,
+            Money::from_str("0.0").unwrap()
+        );
+        assert_eq!(account.balance(Currency::Unspecified).available, amount);
+
+        let tx = ledger.txs.get(&tx_id).unwrap();
+        assert_eq!(tx.tx_status, TxStatus::Resolved);
+    }
+
+    #[test]
+    fn test_handle_resolve_withdrawal_drops_the_pending_refund() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let tx_id = 200;
+        let amount = Money::from_str("10.0").unwrap();
 
+        // Setup: disputed withdrawal, its amount held as a pending refund
+        let mut tx = TransactionRecord::new(
+            tx_id,
+            client_id,
+            amount,
+            TxType::Withdrawal,
+            TxStatus::Normal,
+        );
+        tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
+        ledger.txs.insert(tx_id, tx);
         let account = ledger.get_or_create_account(client_id);
-        assert_eq!(account.held, Money::from_str("0.0").unwrap());
-        assert_eq!(account.available, amount);
+        account.balance_mut(Currency::Unspecified).available = Money::from_str("5.0").unwrap();
+        account.balance_mut(Currency::Unspecified).held = amount;
+
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        // The withdrawal stands: nothing comes back.
+        let balance = ledger
+            .get_or_create_account(client_id)
+            .balance(Currency::Unspecified);
+        assert_eq!(balance.available, Money::from_str("5.0").unwrap());
+        assert_eq!(balance.held, Money::zero());
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Resolved);
@@ -101,10 +218,55 @@ mod tests {
         let tx_id = 200;
 
         let account = ledger.get_or_create_account(client_id);
-        account.locked = true;
+        account.status = AccountStatus::Locked;
+
+        let strict = Policy {
+            locked_accepts: EventKinds::NONE,
+            ..Policy::default()
+        };
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &strict,
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Rejected(Rejection::AccountLocked));
+    }
+
+    #[test]
+    fn test_handle_resolve_finishes_a_dispute_after_the_lock() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let tx_id = 200;
+        let amount = Money::from_str("10.0").unwrap();
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        // Setup: a dispute still open when another one locked the account
+        let mut tx =
+            TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
+        tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
+        ledger.txs.insert(tx_id, tx);
+        let account = ledger.get_or_create_account(client_id);
+        account.status = AccountStatus::Locked;
+        account.balance_mut(Currency::Unspecified).held = amount;
+
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        let account = ledger.get_or_create_account(client_id);
+        assert!(account.is_locked());
+        let balance = account.balance(Currency::Unspecified);
+        assert_eq!((balance.available, balance.held), (amount, Money::zero()));
+        assert_eq!(ledger.txs[&tx_id].tx_status, TxStatus::Resolved);
     }
 
     #[test]
@@ -113,8 +275,15 @@ mod tests {
         let client_id = 1;
         let tx_id = 200;
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Rejected(Rejection::TxNotFound));
     }
 
     #[test]
@@ -133,10 +302,21 @@ mod tests {
             TxStatus::Normal,
         );
         tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
         ledger.txs.insert(tx_id, tx);
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::ClientMismatch)
+        );
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Disputed);
@@ -153,8 +333,18 @@ mod tests {
             TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
         ledger.txs.insert(tx_id, tx);
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::InvalidStatus(TxStatus::Normal))
+        );
 
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Normal);
@@ -170,22 +360,107 @@ mod tests {
         let mut tx =
             TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
         tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
         ledger.txs.insert(tx_id, tx);
 
         // account has less held than amount
         let account = ledger.get_or_create_account(client_id);
-        account.available = Money::from_str("0.0").unwrap();
-        account.held = Money::from_str("20.0").unwrap();
+        account.balance_mut(Currency::Unspecified).available = Money::from_str("0.0").unwrap();
+        account.balance_mut(Currency::Unspecified).held = Money::from_str("20.0").unwrap();
 
-        let result = handle(&mut ledger, client_id, tx_id);
-        assert!(result.is_ok());
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::InsufficientHeldFunds)
+        );
 
         let account = ledger.get_or_create_account(client_id);
-        assert_eq!(account.held, Money::from_str("20.0").unwrap());
-        assert_eq!(account.available, Money::from_str("0.0").unwrap());
+        assert_eq!(
+            account.balance(Currency::Unspecified).held,
+            Money::from_str("20.0").unwrap()
+        );
+        assert_eq!(
+            account.balance(Currency::Unspecified).available,
+            Money::from_str("0.0").unwrap()
+        );
 
         // tx status should remain Disputed if apply failed
         let tx = ledger.txs.get(&tx_id).unwrap();
         assert_eq!(tx.tx_status, TxStatus::Disputed);
     }
+
+    #[test]
+    fn test_handle_resolve_partial_amount_keeps_the_dispute_open() {
+        let mut ledger = Ledger::default();
+        let client_id = 1;
+        let tx_id = 200;
+        let amount = Money::from_str("50.0").unwrap();
+
+        let mut tx =
+            TransactionRecord::new(tx_id, client_id, amount, TxType::Deposit, TxStatus::Normal);
+        tx.set_status(TxStatus::Disputed);
+        tx.disputed = amount;
+        ledger.txs.insert(tx_id, tx);
+        let account = ledger.get_or_create_account(client_id);
+        account.balance_mut(Currency::Unspecified).held = amount;
+
+        let part = Money::from_str("20.0").unwrap();
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            Some(part),
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+
+        let balance = ledger
+            .get_or_create_account(client_id)
+            .balance(Currency::Unspecified);
+        assert_eq!(balance.available, part);
+        assert_eq!(balance.held, Money::from_str("30.0").unwrap());
+        let tx = ledger.txs.get(&tx_id).unwrap();
+        assert_eq!(tx.tx_status, TxStatus::Disputed);
+        assert_eq!((tx.disputed, tx.resolved), (amount - part, part));
+
+        // Only the open part can be resolved.
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            Some(amount),
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Rejected(Rejection::AmountExceeded)
+        );
+
+        // Without an amount, the rest is resolved and the dispute closes.
+        let result = handle(
+            &mut ledger,
+            client_id,
+            tx_id,
+            None,
+            &Policy::default(),
+            &TxStateMachine::default(),
+        );
+        assert_eq!(result.unwrap(), Outcome::Applied);
+        let tx = ledger.txs.get(&tx_id).unwrap();
+        assert_eq!(tx.tx_status, TxStatus::Resolved);
+        assert_eq!((tx.disputed, tx.resolved), (Money::zero(), amount));
+        let balance = ledger
+            .get_or_create_account(client_id)
+            .balance(Currency::Unspecified);
+        assert_eq!((balance.available, balance.held), (amount, Money::zero()));
+    }
 }
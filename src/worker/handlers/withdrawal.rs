@@ -1,45 +1,76 @@
 use crate::{
-    common::{error::AppError, money::Money},
+    common::{
+        currency::Currency,
+        error::AppError,
+        event::EventKind,
+        money::Money,
+        outcome::{Outcome, Rejection},
+    },
     domain::{
-        account::Account,
+        account::Balance,
         ledger::Ledger,
         transaction::{TransactionRecord, TxStatus, TxType},
     },
+    worker::policy::Policy,
 };
 
-pub fn handle(ledger: &mut Ledger, client: u16, tx: u32, amount: Money) -> Result<(), AppError> {
-    // check if account is locked. If there are more common validations, consider moving to a common function
-    if ledger.get_or_create_account(client).is_locked() {
-        return Ok(());
+pub fn handle(
+    ledger: &mut Ledger,
+    client: u16,
+    tx: u32,
+    amount: Money,
+    currency: Currency,
+    policy: &Policy,
+) -> Result<Outcome, AppError> {
+    // the account status decides which events the account accepts
+    if let Err(rejection) = ledger
+        .get_or_create_account(client)
+        .check(EventKind::Withdrawal, policy.locked_accepts)
+    {
+        return Ok(rejection.into());
     }
 
     // Check if transaction already exists (not duplicate)
     if ledger.txs.contains_key(&tx) {
-        return Ok(());
+        return Ok(Rejection::DuplicateTx.into());
     }
 
-    if apply_withdrawal(ledger.get_or_create_account(client), amount) {
-        ledger.txs.insert(
-            tx,
-            TransactionRecord {
-                tx_id: tx,
-                client,
-                amount,
-                tx_type: TxType::Withdrawal,
-                tx_status: TxStatus::Normal,
-            },
-        );
+    // Work on a copy so a rejected withdrawal does not open a balance in a
+    // currency the client never held.
+    let account = ledger.get_or_create_account(client);
+    let mut balance = account.balance(currency);
+    if let Err(rejection) = apply_withdrawal(&mut balance, amount) {
+        return Ok(rejection.into());
     }
-    Ok(())
+    account.balances.insert(currency, balance);
+
+    ledger.txs.insert(
+        tx,
+        TransactionRecord {
+            tx_id: tx,
+            client,
+            amount,
+            currency,
+            tx_type: TxType::Withdrawal,
+            tx_status: TxStatus::Normal,
+            disputes: 0,
+            disputed: Money::zero(),
+            resolved: Money::zero(),
+            charged_back: Money::zero(),
+        },
+    );
+    Ok(Outcome::Applied)
 }
 
-fn apply_withdrawal(acc: &mut Account, amount: Money) -> bool {
-    if acc.available >= amount {
-        acc.available -= amount;
-        true
-    } else {
-        false
+fn apply_withdrawal(balance: &mut Balance, amount: Money) -> Result<(), Rejection> {
+    if balance.available < amount {
+        return Err(Rejection::InsufficientFunds);
     }
+    let available = balance
+        .available
+        .checked_sub(amount)
+        .ok_or(Rejection::Overflow)?;
+    balance.set(available, balance.held)
 }
 
 #[cfg(test)]
@@ -47,7 +78,10 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
-    use crate::domain::transaction::{TransactionRecord, TxStatus, TxType};
+    use crate::domain::{
+        account::AccountStatus,
+        transaction::{TransactionRecord, TxStatus, TxType},
+    };
     // Helper to create Money from integer for tests
     fn money(v: i64) -> Money {
         Money::from_str(&v.to_string()).unwrap()
@@ -55,7 +89,7 @@ mod tests {
     // Helper to seed an account with available funds
     fn seed_available(ledger: &mut Ledger, client: u16, available: Money) {
         let acc = ledger.get_or_create_account(client);
-        acc.available = available;
+        acc.balance_mut(Currency::Unspecified).available = available;
     }
 
     #[test]
@@ -66,11 +100,20 @@ mod tests {
         let tx = 10u32;
 
         seed_available(&mut ledger, client, money(100));
-        handle(&mut ledger, client, tx, money(40)).unwrap();
+        let outcome = handle(
+            &mut ledger,
+            client,
+            tx,
+            money(40),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Applied);
 
         // account changed
         let acc = ledger.get_or_create_account(client);
-        assert_eq!(acc.available, money(60));
+        assert_eq!(acc.balance(Currency::Unspecified).available, money(60));
 
         // tx recorded correctly
         let rec = ledger.txs.get(&tx).expect("tx should be recorded");
@@ -89,10 +132,23 @@ mod tests {
         let tx = 11u32;
 
         seed_available(&mut ledger, client, money(30));
-        handle(&mut ledger, client, tx, money(50)).unwrap();
+        let outcome = handle(
+            &mut ledger,
+            client,
+            tx,
+            money(50),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Rejected(Rejection::InsufficientFunds));
 
         let acc = ledger.get_or_create_account(client);
-        assert_eq!(acc.available, money(30), "available should not go negative");
+        assert_eq!(
+            acc.balance(Currency::Unspecified).available,
+            money(30),
+            "available should not go negative"
+        );
 
         assert!(
             !ledger.txs.contains_key(&tx),
@@ -108,12 +164,29 @@ mod tests {
         let tx = 12u32;
 
         seed_available(&mut ledger, client, money(100));
-        handle(&mut ledger, client, tx, money(10)).unwrap();
-        handle(&mut ledger, client, tx, money(10)).unwrap(); // duplicate
+        handle(
+            &mut ledger,
+            client,
+            tx,
+            money(10),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        let outcome = handle(
+            &mut ledger,
+            client,
+            tx,
+            money(10),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap(); // duplicate
+        assert_eq!(outcome, Outcome::Rejected(Rejection::DuplicateTx));
 
         let acc = ledger.get_or_create_account(client);
         assert_eq!(
-            acc.available,
+            acc.balance(Currency::Unspecified).available,
             money(90),
             "duplicate tx must not withdraw twice"
         );
@@ -132,16 +205,25 @@ mod tests {
         // Seed funds and lock the account.
         {
             let acc = ledger.get_or_create_account(client);
-            acc.available = money(100);
+            acc.balance_mut(Currency::Unspecified).available = money(100);
 
-            acc.locked = true;
+            acc.status = AccountStatus::Locked;
         }
 
-        handle(&mut ledger, client, tx, money(20)).unwrap();
+        let outcome = handle(
+            &mut ledger,
+            client,
+            tx,
+            money(20),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Rejected(Rejection::AccountLocked));
 
         // no balance change
         let acc = ledger.get_or_create_account(client);
-        assert_eq!(acc.available, money(100));
+        assert_eq!(acc.balance(Currency::Unspecified).available, money(100));
 
         // no tx recorded
         assert!(
@@ -164,25 +246,39 @@ mod tests {
                 tx_id: tx,
                 client,
                 amount: money(1),
+                currency: Currency::Unspecified,
                 tx_type: TxType::Withdrawal,
                 tx_status: TxStatus::Normal,
+                disputes: 0,
+                disputed: Money::zero(),
+                resolved: Money::zero(),
+                charged_back: Money::zero(),
             },
         );
 
         // Lock the account and give it funds
         {
             let acc = ledger.get_or_create_account(client);
-            acc.available = money(100);
+            acc.balance_mut(Currency::Unspecified).available = money(100);
             // Adjust as needed
-            acc.locked = true;
+            acc.status = AccountStatus::Locked;
         }
 
-        // Should early-return Ok(()) due to duplicate tx
-        handle(&mut ledger, client, tx, money(50)).unwrap();
+        // Should early-return without applying; the lock check runs first
+        let outcome = handle(
+            &mut ledger,
+            client,
+            tx,
+            money(50),
+            Currency::Unspecified,
+            &Policy::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Rejected(Rejection::AccountLocked));
 
         // balance unchanged
         let acc = ledger.get_or_create_account(client);
-        assert_eq!(acc.available, money(100));
+        assert_eq!(acc.balance(Currency::Unspecified).available, money(100));
 
         // tx unchanged
         let rec = ledger.txs.get(&tx).unwrap();
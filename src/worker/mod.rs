@@ -1,2 +1,3 @@
 mod handlers;
+pub mod policy;
 pub mod processor;
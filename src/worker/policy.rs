@@ -0,0 +1,111 @@
+use crate::{
+    common::event::{EventKind, EventKinds},
+    domain::transaction::{REDISPUTE, TxStateMachine},
+};
+
+/// Event types a locked account accepts by default: resolves and chargebacks,
+/// so disputes still open when a chargeback locks the account can finish.
+pub const IN_FLIGHT_DISPUTES: EventKinds = EventKinds::NONE
+    .with(EventKind::Resolve)
+    .with(EventKind::Chargeback);
+
+/// What a dispute of a withdrawal does.
+///
+/// A disputed withdrawal is one the client says they never made, so the
+/// funds already left the account. Resolves and chargebacks undo whatever the
+/// dispute did, whichever policy is in force when they arrive.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum WithdrawalDisputePolicy {
+    /// Reject the dispute as `not_disputable`; only deposits can be disputed.
+    #[default]
+    Ignore,
+    /// Hold the withdrawn amount as a pending refund: `held` grows by the
+    /// amount while `available` is unchanged. A resolve drops the hold (the
+    /// withdrawal stands); a chargeback credits the amount back to
+    /// `available` and locks the account.
+    Hold,
+}
+
+/// What a dispute of a deposit does when the client no longer has the
+/// deposited funds available, e.g. after withdrawing them.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum NegativeAvailablePolicy {
+    /// Hold the whole amount and let `available` go negative.
+    #[default]
+    Allow,
+    /// Reject the dispute as `insufficient_funds`.
+    Reject,
+    /// Hold only what is available; the transaction's disputed amount is
+    /// that part. A dispute with nothing available is rejected as
+    /// `insufficient_funds`.
+    HoldAvailable,
+}
+
+/// Rules the processor applies where the event alone does not decide.
+///
+/// Serialized field by field with the flags' values, e.g.
+/// `{"withdrawal_disputes":"hold","redispute":true,...}`, for the journal
+/// header; missing fields take their defaults.
+#[derive(clap::Args, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Policy {
+    /// What a dispute of a withdrawal does.
+    #[arg(long, value_enum, default_value_t)]
+    pub withdrawal_disputes: WithdrawalDisputePolicy,
+
+    /// What a dispute does when it would drive `available` negative.
+    #[arg(long, value_enum, default_value_t)]
+    pub negative_available: NegativeAvailablePolicy,
+
+    /// Allow a resolved transaction to be disputed again (second presentment).
+    #[arg(long)]
+    pub redispute: bool,
+
+    /// Most disputes one transaction may go through; unlimited by default.
+    #[arg(long, value_name = "N")]
+    pub max_disputes: Option<u32>,
+
+    /// Event types a locked account still accepts, comma-separated, or `none`.
+    #[arg(long, value_name = "TYPES", default_value_t = IN_FLIGHT_DISPUTES)]
+    pub locked_accepts: EventKinds,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            withdrawal_disputes: WithdrawalDisputePolicy::default(),
+            negative_available: NegativeAvailablePolicy::default(),
+            redispute: false,
+            max_disputes: None,
+            locked_accepts: IN_FLIGHT_DISPUTES,
+        }
+    }
+}
+
+impl Policy {
+    /// The transaction status transitions this policy allows.
+    pub fn tx_state_machine(&self) -> TxStateMachine {
+        let mut machine = TxStateMachine::default();
+        if self.redispute {
+            machine = machine.with_transition(REDISPUTE);
+        }
+        if let Some(max) = self.max_disputes {
+            machine = machine.with_max_disputes(max);
+        }
+        machine
+    }
+}
@@ -1,46 +1,322 @@
+#[cfg(feature = "serde")]
+use crate::io::journal::Journal;
 use crate::{
-    common::{error::AppError, event::TransactionEvent},
-    domain::ledger::Ledger,
-    worker::handlers::{chargeback, deposit, dispute, resolve, withdrawal},
+    common::{error::AppError, event::TransactionEvent, outcome::Outcome},
+    domain::{ledger::Ledger, transaction::TxStateMachine},
+    worker::{
+        handlers::{chargeback, deposit, dispute, resolve, withdrawal},
+        policy::Policy,
+    },
 };
 
 #[derive(Debug, Default)]
-pub struct Processor {}
+pub struct Processor {
+    policy: Policy,
+    /// Built from `policy`.
+    machine: TxStateMachine,
+    /// Write-ahead journal every event is appended to before it is applied.
+    #[cfg(feature = "serde")]
+    journal: Option<Journal>,
+}
 impl Processor {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Applies events under `policy` instead of the default one.
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self.machine = policy.tx_state_machine();
+        self
     }
 
+    /// Appends every event to `journal` before applying it.
+    #[cfg(feature = "serde")]
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Closes the journal, if any, forcing it to disk as its policy allows.
+    pub fn finish(self) -> Result<(), AppError> {
+        #[cfg(feature = "serde")]
+        if let Some(journal) = self.journal {
+            journal.close().map_err(AppError::Output)?;
+        }
+        Ok(())
+    }
+
+    /// Routes a single event to its handler.
+    ///
+    /// Returns `Outcome::Applied` when the ledger changed, or
+    /// `Outcome::Rejected` with the reason the event was ignored. An `Err`
+    /// is only returned for failures that should stop processing.
     pub fn process(
         &mut self,
         ledger: &mut Ledger,
         event: TransactionEvent,
-    ) -> Result<(), AppError> {
+    ) -> Result<Outcome, AppError> {
+        #[cfg(feature = "serde")]
+        if let Some(journal) = self.journal.as_mut() {
+            journal.append(&event).map_err(AppError::Output)?;
+        }
+
         match event {
             TransactionEvent::Deposit {
                 tx: tx_id,
                 client,
                 amount,
-            } => {
-                deposit::handle(ledger, client, tx_id, amount)?;
-            }
+                currency,
+            } => deposit::handle(ledger, client, tx_id, amount, currency, &self.policy),
             TransactionEvent::Withdrawal {
                 tx: tx_id,
                 client,
                 amount,
-            } => {
-                withdrawal::handle(ledger, client, tx_id, amount)?;
-            }
-            TransactionEvent::Dispute { tx: tx_id, client } => {
-                dispute::handle(ledger, client, tx_id)?;
-            }
-            TransactionEvent::Resolve { tx: tx_id, client } => {
-                resolve::handle(ledger, client, tx_id)?;
-            }
-            TransactionEvent::Chargeback { tx: tx_id, client } => {
-                chargeback::handle(ledger, client, tx_id)?;
+                currency,
+            } => withdrawal::handle(ledger, client, tx_id, amount, currency, &self.policy),
+            TransactionEvent::Dispute {
+                tx: tx_id,
+                client,
+                amount,
+            } => dispute::handle(ledger, client, tx_id, amount, &self.policy, &self.machine),
+            TransactionEvent::Resolve {
+                tx: tx_id,
+                client,
+                amount,
+            } => resolve::handle(ledger, client, tx_id, amount, &self.policy, &self.machine),
+            TransactionEvent::Chargeback {
+                tx: tx_id,
+                client,
+                amount,
+            } => chargeback::handle(ledger, client, tx_id, amount, &self.policy, &self.machine),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{
+        common::{currency::Currency, money::Money, outcome::Rejection},
+        domain::transaction::TxStatus,
+    };
+
+    #[test]
+    fn process_reports_applied_and_rejected_outcomes() {
+        let mut ledger = Ledger::new();
+        let mut processor = Processor::new();
+        let amount = Money::from_str("1.0").unwrap();
+
+        let deposit = TransactionEvent::Deposit {
+            client: 1,
+            tx: 1,
+            amount,
+            currency: Currency::Unspecified,
+        };
+        assert_eq!(
+            processor.process(&mut ledger, deposit).unwrap(),
+            Outcome::Applied
+        );
+
+        let withdrawal = TransactionEvent::Withdrawal {
+            client: 1,
+            tx: 2,
+            amount: Money::from_str("5.0").unwrap(),
+            currency: Currency::Unspecified,
+        };
+        assert_eq!(
+            processor.process(&mut ledger, withdrawal).unwrap(),
+            Outcome::Rejected(Rejection::InsufficientFunds)
+        );
+
+        let resolve = TransactionEvent::Resolve {
+            client: 1,
+            tx: 1,
+            amount: None,
+        };
+        assert_eq!(
+            processor.process(&mut ledger, resolve).unwrap(),
+            Outcome::Rejected(Rejection::InvalidStatus(
+                crate::domain::transaction::TxStatus::Normal
+            ))
+        );
+    }
+
+    #[test]
+    fn balances_are_kept_per_currency() {
+        let mut ledger = Ledger::new();
+        let mut processor = Processor::new();
+        let event = |tx, amount: &str, currency, deposit: bool| {
+            let amount = Money::from_str(amount).unwrap();
+            if deposit {
+                TransactionEvent::Deposit {
+                    client: 1,
+                    tx,
+                    amount,
+                    currency,
+                }
+            } else {
+                TransactionEvent::Withdrawal {
+                    client: 1,
+                    tx,
+                    amount,
+                    currency,
+                }
             }
+        };
+
+        for ev in [
+            event(1, "10", Currency::Usd, true),
+            event(2, "500", Currency::Jpy, true),
+        ] {
+            assert_eq!(
+                processor.process(&mut ledger, ev).unwrap(),
+                Outcome::Applied
+            );
         }
-        Ok(())
+
+        // USD funds do not cover a EUR withdrawal.
+        assert_eq!(
+            processor
+                .process(&mut ledger, event(3, "1", Currency::Eur, false))
+                .unwrap(),
+            Outcome::Rejected(Rejection::InsufficientFunds)
+        );
+
+        // A dispute holds funds in the currency of the disputed deposit.
+        let dispute = TransactionEvent::Dispute {
+            client: 1,
+            tx: 2,
+            amount: None,
+        };
+        assert_eq!(
+            processor.process(&mut ledger, dispute).unwrap(),
+            Outcome::Applied
+        );
+
+        let acc = &ledger.accounts()[&1];
+        assert_eq!(
+            acc.balance(Currency::Usd).available,
+            Money::from_str("10").unwrap()
+        );
+        assert_eq!(acc.balance(Currency::Jpy).available, Money::zero());
+        assert_eq!(
+            acc.balance(Currency::Jpy).held,
+            Money::from_str("500").unwrap()
+        );
+        assert!(!acc.balances.contains_key(&Currency::Eur));
+    }
+
+    #[test]
+    fn redisputes_follow_the_policy_and_its_limit() {
+        let mut ledger = Ledger::new();
+        let mut processor = Processor::new().with_policy(Policy {
+            redispute: true,
+            max_disputes: Some(2),
+            ..Policy::default()
+        });
+        let deposit = TransactionEvent::Deposit {
+            client: 1,
+            tx: 1,
+            amount: Money::from_str("2.0").unwrap(),
+            currency: Currency::Unspecified,
+        };
+        processor.process(&mut ledger, deposit).unwrap();
+
+        let dispute = || TransactionEvent::Dispute {
+            client: 1,
+            tx: 1,
+            amount: None,
+        };
+        let resolve = || TransactionEvent::Resolve {
+            client: 1,
+            tx: 1,
+            amount: None,
+        };
+        let outcomes: Vec<Outcome> = [dispute(), resolve(), dispute(), resolve(), dispute()]
+            .into_iter()
+            .map(|ev| processor.process(&mut ledger, ev).unwrap())
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                Outcome::Applied,
+                Outcome::Applied,
+                Outcome::Applied,
+                Outcome::Applied,
+                Outcome::Rejected(Rejection::DisputeLimit),
+            ]
+        );
+        assert_eq!(ledger.txs[&1].disputes, 2);
+        let balance = ledger.accounts()[&1].balance(Currency::Unspecified);
+        assert_eq!(balance.available, Money::from_str("2.0").unwrap());
+        assert_eq!(balance.held, Money::zero());
+
+        // Without the policy, a resolved transaction stays resolved.
+        let mut processor = Processor::new();
+        assert_eq!(
+            processor.process(&mut ledger, dispute()).unwrap(),
+            Outcome::Rejected(Rejection::InvalidStatus(TxStatus::Resolved))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn replaying_the_journal_reproduces_the_ledger() {
+        use crate::io::journal::{self, FsyncPolicy};
+
+        let path = std::env::temp_dir().join(format!(
+            "transaction_parser_processor_journal_{}.jsonl",
+            std::process::id()
+        ));
+        let amount = Money::from_str("3.0").unwrap();
+        let events = || {
+            [
+                TransactionEvent::Deposit {
+                    client: 1,
+                    tx: 1,
+                    amount,
+                    currency: Currency::Unspecified,
+                },
+                TransactionEvent::Dispute {
+                    client: 1,
+                    tx: 1,
+                    amount: None,
+                },
+                // Rejected, but journaled all the same.
+                TransactionEvent::Withdrawal {
+                    client: 1,
+                    tx: 2,
+                    amount,
+                    currency: Currency::Unspecified,
+                },
+            ]
+        };
+
+        let mut ledger = Ledger::new();
+        let mut processor = Processor::new().with_journal(
+            journal::Journal::create(&path, FsyncPolicy::Always, &Policy::default()).unwrap(),
+        );
+        for ev in events() {
+            processor.process(&mut ledger, ev).unwrap();
+        }
+        processor.finish().unwrap();
+
+        let replay = journal::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replay.events.len(), 3);
+
+        let mut replayed = Ledger::new();
+        let mut processor = Processor::new();
+        for ev in replay.events {
+            processor.process(&mut replayed, ev).unwrap();
+        }
+        assert_eq!(replayed, ledger);
+        assert_eq!(
+            replayed.accounts()[&1].balance(Currency::Unspecified).held,
+            amount
+        );
     }
 }
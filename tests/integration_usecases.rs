@@ -21,8 +21,51 @@ fn run_case(input_csv: &str) -> String {
     }
 
     let mut out = Vec::<u8>::new();
-    transaction_parser::io::writer::write_accounts(&mut out, ledger.accounts())
-        .expect("failed to write output CSV");
+    transaction_parser::io::writer::write_accounts(
+        &mut out,
+        ledger.accounts(),
+        transaction_parser::io::writer::ReportOptions::default(),
+    )
+    .expect("failed to write output CSV");
+    String::from_utf8(out).expect("output was not valid UTF-8")
+}
+
+fn run_case_rejects(input_csv: &str) -> String {
+    let mut ledger = Ledger::new();
+    let mut worker = transaction_parser::worker::processor::Processor::new();
+
+    let rdr = Cursor::new(input_csv.as_bytes());
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(rdr);
+
+    let mut out = Vec::<u8>::new();
+    {
+        let mut rejects = transaction_parser::io::rejects::RejectWriter::new(&mut out);
+        for row in transaction_parser::io::reader::read_rows(
+            &mut csv_reader,
+            transaction_parser::common::money::RoundingPolicy::default(),
+        ) {
+            let row = row.expect("failed to read input row");
+            let ev = row.event.expect("failed to parse input row");
+            let outcome = worker
+                .process(&mut ledger, ev)
+                .expect("failed to process event");
+            if let Some(reason) = outcome.rejection() {
+                rejects
+                    .write(
+                        "case2_input.csv",
+                        row.line,
+                        &row.raw,
+                        reason.code(),
+                        &reason.to_string(),
+                    )
+                    .expect("failed to write rejects CSV");
+            }
+        }
+        rejects.flush().expect("failed to flush rejects CSV");
+    }
     String::from_utf8(out).expect("output was not valid UTF-8")
 }
 
@@ -66,3 +109,455 @@ fn case3_floating_point_precision() {
 
     assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
 }
+
+#[test]
+fn case4_balances_per_client_and_currency() {
+    let input = fs::read_to_string("tests/fixtures/case4_currencies_input.csv").unwrap();
+    let expected = fs::read_to_string("tests/fixtures/case4_currencies_expected.csv").unwrap();
+
+    let actual = run_case(&input);
+
+    assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
+}
+
+#[test]
+fn case2_rejects_report_lists_rows_ignored_after_lock() {
+    let input = fs::read_to_string("tests/fixtures/case2_input.csv").unwrap();
+    let expected = fs::read_to_string("tests/fixtures/case2_rejects_expected.csv").unwrap();
+
+    let actual = run_case_rejects(&input);
+
+    assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
+}
+
+#[test]
+fn case1_sharded_inputs_are_processed_in_order_into_one_ledger() {
+    let expected = fs::read_to_string("tests/fixtures/case1_expected.csv").unwrap();
+    let output = std::env::temp_dir().join(format!(
+        "transaction_parser_sharded_{}.csv",
+        std::process::id()
+    ));
+
+    let summary = transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case1_part1_input.csv",
+        "tests/fixtures/case1_part2_input.csv",
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .expect("run failed");
+    let actual = fs::read_to_string(&output).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert!(!summary.is_partial());
+    assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
+}
+
+#[test]
+fn truncated_compressed_input_fails_the_run_even_when_skipping_bad_rows() {
+    use std::io::Write;
+
+    let input = fs::read("tests/fixtures/case1_input.csv").unwrap();
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gz.write_all(&input).unwrap();
+    let mut gz = gz.finish().unwrap();
+    gz.truncate(gz.len() - 12);
+    let path = std::env::temp_dir().join(format!(
+        "transaction_parser_truncated_{}.csv.gz",
+        std::process::id()
+    ));
+    fs::write(&path, gz).unwrap();
+
+    let result = transaction_parser::app::run([
+        "transaction_parser",
+        path.to_str().unwrap(),
+        "--on-parse-error=skip",
+    ]);
+    fs::remove_file(&path).unwrap();
+
+    let err = result.expect_err("a truncated input must not succeed");
+    assert_eq!(
+        err.exit_code(),
+        transaction_parser::common::error::exit_code::OPEN_INPUT
+    );
+}
+
+#[test]
+fn report_file_errors_name_the_file_and_fail_as_output_errors() {
+    let output = std::env::temp_dir().join(format!(
+        "transaction_parser_report_errors_{}.csv",
+        std::process::id()
+    ));
+    let missing = std::env::temp_dir()
+        .join(format!("transaction_parser_missing_{}", std::process::id()))
+        .join("report.csv");
+    let missing = missing.to_str().unwrap();
+    let mut cases = vec![
+        ("--rejects", missing),
+        ("--totals", missing),
+        ("--negative-available-report", missing),
+    ];
+    // Writes to /dev/full fail once they reach the device.
+    if cfg!(target_os = "linux") {
+        cases.push(("--rejects", "/dev/full"));
+        cases.push(("--totals", "/dev/full"));
+    }
+
+    for (flag, path) in cases {
+        let err = transaction_parser::app::run([
+            "transaction_parser",
+            "tests/fixtures/case2_input.csv",
+            "--output",
+            output.to_str().unwrap(),
+            flag,
+            path,
+        ])
+        .expect_err("writing the report must fail");
+        let _ = fs::remove_file(&output);
+
+        assert_eq!(
+            err.exit_code(),
+            transaction_parser::common::error::exit_code::OUTPUT,
+            "{flag} {path}: {err}"
+        );
+        assert!(err.to_string().contains(path), "{flag}: {err}");
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn case2_jsonl_input_matches_csv_result() {
+    let expected = fs::read_to_string("tests/fixtures/case2_expected.csv").unwrap();
+    let output = std::env::temp_dir().join(format!(
+        "transaction_parser_jsonl_{}.csv",
+        std::process::id()
+    ));
+
+    // Format is auto-detected from the `.jsonl` extension.
+    transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case2_input.jsonl",
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .expect("run failed");
+    let actual = fs::read_to_string(&output).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn case2_disputes_carry_over_between_days_through_a_snapshot() {
+    let expected = fs::read_to_string("tests/fixtures/case2_expected.csv").unwrap();
+    let tmp = |name: &str| {
+        std::env::temp_dir().join(format!(
+            "transaction_parser_state_{name}_{}",
+            std::process::id()
+        ))
+    };
+    let (state, day1_out, day2_out) = (tmp("ledger.json"), tmp("day1.csv"), tmp("day2.csv"));
+
+    // Day 1 leaves both deposits disputed; day 2 resolves and charges them back.
+    transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case2_day1_input.csv",
+        "--output",
+        day1_out.to_str().unwrap(),
+        "--save-state",
+        state.to_str().unwrap(),
+    ])
+    .expect("day 1 failed");
+    transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case2_day2_input.csv",
+        "--output",
+        day2_out.to_str().unwrap(),
+        "--load-state",
+        state.to_str().unwrap(),
+    ])
+    .expect("day 2 failed");
+    let actual = fs::read_to_string(&day2_out).unwrap();
+    for path in [&state, &day1_out, &day2_out] {
+        fs::remove_file(path).unwrap();
+    }
+
+    assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn case2_recover_replays_the_journal_on_top_of_the_snapshot() {
+    use transaction_parser::common::error::AppError;
+
+    let tmp = |name: &str| {
+        std::env::temp_dir().join(format!(
+            "transaction_parser_recover_{name}_{}",
+            std::process::id()
+        ))
+    };
+    let (day1, day2, journal, out) = (
+        tmp("day1.json"),
+        tmp("day2.json"),
+        tmp("day2.jsonl"),
+        tmp("out.csv"),
+    );
+    let path = |p: &std::path::PathBuf| p.to_str().unwrap().to_string();
+
+    transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case2_day1_input.csv",
+        "--output",
+        &path(&out),
+        "--save-state",
+        &path(&day1),
+    ])
+    .expect("day 1 failed");
+    transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case2_day2_input.csv",
+        "--output",
+        &path(&out),
+        "--load-state",
+        &path(&day1),
+        "--journal",
+        &path(&journal),
+        "--save-state",
+        &path(&day2),
+    ])
+    .expect("day 2 failed");
+
+    let recovered = transaction_parser::app::run([
+        "transaction_parser",
+        "recover",
+        &path(&journal),
+        "--load-state",
+        &path(&day1),
+        "--check",
+        &path(&day2),
+    ]);
+    // Without the day 1 snapshot, the disputes the journal refers to are missing.
+    let without_base = transaction_parser::app::run([
+        "transaction_parser",
+        "recover",
+        &path(&journal),
+        "--check",
+        &path(&day2),
+    ]);
+    for p in [&day1, &day2, &journal, &out] {
+        fs::remove_file(p).unwrap();
+    }
+
+    assert!(!recovered.expect("recover failed").is_partial());
+    match without_base {
+        Err(AppError::Process(msg)) => {
+            assert!(msg.contains("does not match"), "{msg}");
+            assert!(msg.contains("client(s) [1]"), "{msg}");
+        }
+        other => panic!("expected a mismatch, got {other:?}"),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn case5_frozen_and_closed_accounts_from_a_snapshot() {
+    let expected = fs::read_to_string("tests/fixtures/case5_statuses_expected.csv").unwrap();
+    let output = std::env::temp_dir().join(format!(
+        "transaction_parser_statuses_{}.csv",
+        std::process::id()
+    ));
+
+    // Client 1 is frozen (no withdrawals), client 2 is closed (nothing).
+    transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case1_input.csv",
+        "--load-state",
+        "tests/fixtures/case5_statuses_state.json",
+        "--status-column",
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .expect("run failed");
+    let actual = fs::read_to_string(&output).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn case5_snapshot_breaking_ledger_invariants_is_a_parse_error() {
+    // Transaction 1 has more disputed and charged back than its amount.
+    let result = transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case1_input.csv",
+        "--load-state",
+        "tests/fixtures/case5_invalid_state.json",
+    ]);
+
+    let err = result.expect_err("an invalid snapshot must not load");
+    assert_eq!(
+        err.exit_code(),
+        transaction_parser::common::error::exit_code::PARSE
+    );
+    let msg = err.to_string();
+    assert!(msg.contains("case5_invalid_state.json: tx 1"), "{msg}");
+}
+
+#[test]
+fn case6_withdrawal_disputes_under_the_hold_policy() {
+    let expected =
+        fs::read_to_string("tests/fixtures/case6_withdrawal_disputes_expected.csv").unwrap();
+    let output = std::env::temp_dir().join(format!(
+        "transaction_parser_withdrawal_disputes_{}.csv",
+        std::process::id()
+    ));
+
+    // Client 1 wins a chargeback on a withdrawal; client 2's dispute is resolved.
+    transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case6_withdrawal_disputes_input.csv",
+        "--withdrawal-disputes",
+        "hold",
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .expect("run failed");
+    let actual = fs::read_to_string(&output).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn case6_recover_applies_the_policy_recorded_in_the_journal() {
+    let tmp = |name: &str| {
+        std::env::temp_dir().join(format!(
+            "transaction_parser_recover_policy_{name}_{}",
+            std::process::id()
+        ))
+    };
+    let (state, journal, out) = (tmp("state.json"), tmp("run.jsonl"), tmp("out.csv"));
+    let path = |p: &std::path::PathBuf| p.to_str().unwrap().to_string();
+
+    transaction_parser::app::run([
+        "transaction_parser",
+        "tests/fixtures/case6_withdrawal_disputes_input.csv",
+        "--withdrawal-disputes",
+        "hold",
+        "--output",
+        &path(&out),
+        "--journal",
+        &path(&journal),
+        "--save-state",
+        &path(&state),
+    ])
+    .expect("run failed");
+
+    // No policy flags: the withdrawal disputes replay under `hold` anyway.
+    let recovered = transaction_parser::app::run([
+        "transaction_parser",
+        "recover",
+        &path(&journal),
+        "--check",
+        &path(&state),
+    ]);
+    for p in [&state, &journal, &out] {
+        fs::remove_file(p).unwrap();
+    }
+
+    assert!(!recovered.expect("recover failed").is_partial());
+}
+
+#[test]
+fn case7_partial_disputes_resolves_and_chargebacks() {
+    // Client 1 charges back 20.0 of a 30.0 dispute after resolving 10.0;
+    // client 2's dispute for more than the deposit is refused, and one for
+    // exactly its amount is then resolved in two steps.
+    let input = fs::read_to_string("tests/fixtures/case7_partial_disputes_input.csv").unwrap();
+    let expected =
+        fs::read_to_string("tests/fixtures/case7_partial_disputes_expected.csv").unwrap();
+
+    let actual = run_case(&input);
+
+    assert_eq!(normalize_csv(&actual), normalize_csv(&expected));
+}
+
+#[test]
+fn case8_disputes_of_withdrawn_funds_under_each_negative_available_policy() {
+    let run = |policy: &str| {
+        let dir = std::env::temp_dir();
+        let id = format!("{}_{policy}", std::process::id());
+        let output = dir.join(format!("transaction_parser_negative_{id}.csv"));
+        let report = dir.join(format!("transaction_parser_negative_report_{id}.csv"));
+        transaction_parser::app::run([
+            "transaction_parser",
+            "tests/fixtures/case8_negative_available_input.csv",
+            "--negative-available",
+            policy,
+            "--negative-available-report",
+            report.to_str().unwrap(),
+            "--output",
+            output.to_str().unwrap(),
+        ])
+        .expect("run failed");
+        let balances = fs::read_to_string(&output).unwrap();
+        let negative = fs::read_to_string(&report).unwrap();
+        fs::remove_file(&output).unwrap();
+        fs::remove_file(&report).unwrap();
+        (balances, negative)
+    };
+
+    // Clients 1 and 2 withdrew funds they then disputed.
+    let (balances, negative) = run("allow");
+    let expected =
+        fs::read_to_string("tests/fixtures/case8_negative_available_expected.csv").unwrap();
+    let expected_negative =
+        fs::read_to_string("tests/fixtures/case8_negative_available_report_expected.csv").unwrap();
+    assert_eq!(normalize_csv(&balances), normalize_csv(&expected));
+    assert_eq!(normalize_csv(&negative), normalize_csv(&expected_negative));
+
+    // Only client 1's remaining 30.0 is held; client 2 has nothing left to hold.
+    let (balances, negative) = run("hold-available");
+    let expected = fs::read_to_string("tests/fixtures/case8_hold_available_expected.csv").unwrap();
+    assert_eq!(normalize_csv(&balances), normalize_csv(&expected));
+    assert_eq!(
+        normalize_csv(&negative),
+        "client,currency,available,held,total"
+    );
+}
+
+#[test]
+fn case9_open_disputes_finish_after_a_chargeback_locks_the_account() {
+    let run = |extra: &[&str]| {
+        let output = std::env::temp_dir().join(format!(
+            "transaction_parser_locked_{}_{}.csv",
+            std::process::id(),
+            extra.len()
+        ));
+        let mut args = vec![
+            "transaction_parser",
+            "tests/fixtures/case9_locked_input.csv",
+            "--output",
+            output.to_str().unwrap(),
+        ];
+        args.extend_from_slice(extra);
+        transaction_parser::app::run(args).expect("run failed");
+        let actual = fs::read_to_string(&output).unwrap();
+        fs::remove_file(&output).unwrap();
+        actual
+    };
+
+    // By default the resolve of tx 2 still goes through; the deposit does not.
+    let expected = fs::read_to_string("tests/fixtures/case9_locked_expected.csv").unwrap();
+    assert_eq!(normalize_csv(&run(&[])), normalize_csv(&expected));
+
+    // Accepting nothing once locked leaves tx 2's amount held.
+    let expected = fs::read_to_string("tests/fixtures/case9_locked_strict_expected.csv").unwrap();
+    assert_eq!(
+        normalize_csv(&run(&["--locked-accepts", "none"])),
+        normalize_csv(&expected)
+    );
+}